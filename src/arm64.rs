@@ -0,0 +1,141 @@
+// AArch64 support for binary scans: encodings for the instruction tokens
+// allowed in signatures ("[adrp x8]") and a decoder for the few instructions
+// that form addresses, so matches can be resolved to what they reference.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    // Targets are relative to the instruction's own address.
+    Adrp { rd: u32, offset: i64 },
+    Adr { rd: u32, offset: i64 },
+    Branch { offset: i64 },
+    // `offset` is the (already scaled) unsigned immediate.
+    AddImmediate { rd: u32, rn: u32, offset: u64 },
+    Load { rt: u32, rn: u32, offset: u64 },
+    Other,
+}
+
+fn sign_extend(value: u32, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value as i64) << shift) >> shift
+}
+
+pub fn decode(word: u32) -> Instruction {
+    let rd = word & 0x1F;
+    let rn = (word >> 5) & 0x1F;
+
+    if word & 0x1F00_0000 == 0x1000_0000 {
+        let immediate = sign_extend((((word >> 5) & 0x7FFFF) << 2) | ((word >> 29) & 0x3), 21);
+        return if word & 0x8000_0000 != 0 {
+            Instruction::Adrp { rd, offset: immediate << 12 }
+        } else {
+            Instruction::Adr { rd, offset: immediate }
+        };
+    }
+    if word & 0x7C00_0000 == 0x1400_0000 {
+        // B and BL
+        return Instruction::Branch {
+            offset: sign_extend(word & 0x03FF_FFFF, 26) << 2,
+        };
+    }
+    if word & 0x7F80_0000 == 0x1100_0000 {
+        // ADD (immediate), 32 or 64 bit, optionally shifted by 12
+        let shift = if word & 0x0040_0000 != 0 { 12 } else { 0 };
+        return Instruction::AddImmediate {
+            rd,
+            rn,
+            offset: (((word >> 10) & 0xFFF) as u64) << shift,
+        };
+    }
+    let size = word >> 30;
+    let simd = word & 0x0400_0000 != 0;
+    let is_load = match (simd, (word >> 22) & 0x3) {
+        (_, 0b01) => true,
+        // LDRSB/LDRSH/LDRSW to X; PRFM shares the 64-bit encoding.
+        (false, 0b10) => size != 3,
+        // LDRSB/LDRSH to W
+        (false, 0b11) => size < 2,
+        // LDR Q; opc 10 is the matching store.
+        (true, 0b11) => size == 0,
+        _ => false,
+    };
+    if word & 0x3B00_0000 == 0x3900_0000 && is_load {
+        // LDR/LDRB/LDRH, their sign-extending forms and the SIMD loads,
+        // unsigned offset. The immediate is scaled by the access size.
+        let mut scale = size;
+        if simd && word & 0x0080_0000 != 0 {
+            scale += 4; // 128-bit Q register
+        }
+        return Instruction::Load {
+            rt: rd,
+            rn,
+            offset: (((word >> 10) & 0xFFF) as u64) << scale,
+        };
+    }
+    Instruction::Other
+}
+
+fn register(operand: Option<&str>, allow_sp: bool) -> Result<Option<u32>, String> {
+    let Some(operand) = operand else {
+        return Ok(None);
+    };
+    let lower = operand.to_ascii_lowercase();
+    match lower.as_str() {
+        "?" | "x?" | "w?" => return Ok(None),
+        "xzr" | "wzr" if !allow_sp => return Ok(Some(31)),
+        "sp" | "wsp" if allow_sp => return Ok(Some(31)),
+        _ => {}
+    }
+    lower
+        .strip_prefix('x')
+        .or_else(|| lower.strip_prefix('w'))
+        .and_then(|number| number.parse::<u32>().ok())
+        .filter(|&number| number <= 30)
+        .map(Some)
+        .ok_or_else(|| format!("unknown register '{}'", operand))
+}
+
+// Encodes "adrp x8" or "ldr x0, [x8, #?]" as a value/mask pair over the
+// little-endian instruction word. Immediates are always wildcards, so any
+// written "#..." are ignored; registers left out or written "?" are
+// wildcards too.
+pub fn instruction_pattern(text: &str) -> Result<(u32, u32), String> {
+    let mut parts = text
+        .split(|c: char| matches!(c, ',' | '[' | ']') || c.is_whitespace())
+        .filter(|part| !part.is_empty() && !part.starts_with('#'));
+    let mnemonic = parts
+        .next()
+        .ok_or_else(|| "empty instruction".to_string())?
+        .to_ascii_lowercase();
+    let operands: Vec<&str> = parts.collect();
+    let is_w = operands.first().is_some_and(|r| r.starts_with(['w', 'W']));
+
+    let (value, mask, max_operands) = match mnemonic.as_str() {
+        "adrp" => (0x9000_0000, 0x9F00_0000, 1),
+        "adr" => (0x1000_0000, 0x9F00_0000, 1),
+        "b" => (0x1400_0000, 0xFC00_0000, 0),
+        "bl" => (0x9400_0000, 0xFC00_0000, 0),
+        "ret" => (0xD65F_03C0, 0xFFFF_FFFF, 0),
+        "nop" => (0xD503_201F, 0xFFFF_FFFF, 0),
+        "add" if is_w => (0x1100_0000, 0xFF80_0000, 2),
+        "add" => (0x9100_0000, 0xFF80_0000, 2),
+        "ldr" if is_w => (0xB940_0000, 0xFFC0_0000, 2),
+        "ldr" => (0xF940_0000, 0xFFC0_0000, 2),
+        "str" if is_w => (0xB900_0000, 0xFFC0_0000, 2),
+        "str" => (0xF900_0000, 0xFFC0_0000, 2),
+        _ => return Err(format!("unsupported instruction '{}'", mnemonic)),
+    };
+    if operands.len() > max_operands {
+        return Err(format!("too many operands for '{}'", mnemonic));
+    }
+
+    let (mut value, mut mask) = (value, mask);
+    if let Some(rd) = register(operands.first().copied(), mnemonic == "add")? {
+        value |= rd;
+        mask |= 0x1F;
+    }
+    if let Some(rn) = register(operands.get(1).copied(), true)? {
+        value |= rn << 5;
+        mask |= 0x1F << 5;
+    }
+    Ok((value, mask))
+}
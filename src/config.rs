@@ -1,170 +1,179 @@
-use crate::models::{GameVariant, OffsetCategory, Target};
+use crate::input::Input;
+use crate::models::{GameVariant, OffsetCategory, Target, TargetSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-pub fn get_game_config(variant: GameVariant) -> Vec<(OffsetCategory, Vec<Target>)> {
-    match variant {
-        GameVariant::FreeFire => get_freefire_offsets(),
-        GameVariant::FreeFireMax => get_freefire_max_offsets(),
-        GameVariant::FreeFireTela => get_freefire_tela_offsets(),
+const BUILTIN_TARGETS: &[(&str, &str)] = &[
+    ("freefire", include_str!("../profiles/freefire.toml")),
+    ("freefire_max", include_str!("../profiles/freefire_max.toml")),
+    ("freefire_tela", include_str!("../profiles/freefire_tela.toml")),
+];
+
+pub type TargetConfig = Vec<(OffsetCategory, Vec<Target>)>;
+
+pub fn get_game_config(variant: GameVariant) -> TargetConfig {
+    let name = match variant {
+        GameVariant::FreeFire => "freefire",
+        GameVariant::FreeFireMax => "freefire_max",
+        GameVariant::FreeFireTela => "freefire_tela",
+    };
+    load_named_target_set(name)
+        .and_then(resolve_target_set)
+        .expect("built-in target set is valid")
+}
+
+pub fn parse_target_set(source: &str, json: bool) -> Result<TargetSet, String> {
+    if json {
+        serde_json::from_str(source).map_err(|e| e.to_string())
+    } else {
+        toml::from_str(source).map_err(|e| e.to_string())
+    }
+}
+
+pub fn load_target_set(path: &Path) -> Result<TargetSet, String> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("Error reading target file {}: {}", path.display(), e))?;
+    let json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    parse_target_set(&source, json)
+        .map_err(|e| format!("Invalid target file {}: {}", path.display(), e))
+}
+
+// Built-in sets win over files so `extends = "freefire"` always means the
+// shipped profile.
+pub fn load_named_target_set(name: &str) -> Result<TargetSet, String> {
+    match BUILTIN_TARGETS.iter().find(|(builtin, _)| *builtin == name) {
+        Some((_, source)) => parse_target_set(source, false)
+            .map_err(|e| format!("Invalid built-in target set {}: {}", name, e)),
+        None => load_target_set(&find_target_file(name)?),
+    }
+}
+
+// Which file (or built-in set) `load_named_target_set(name)` reads.
+fn target_set_source(name: &str) -> Result<String, String> {
+    if builtin_target_set_names().any(|builtin| builtin == name) {
+        return Ok(format!("built-in {}", name));
     }
+    let path = find_target_file(name)?;
+    Ok(fs::canonicalize(&path).unwrap_or(path).display().to_string())
 }
 
-fn get_freefire_offsets() -> Vec<(OffsetCategory, Vec<Target>)> {
-    vec![
-        (
-            OffsetCategory::Core,
-            vec![
-                Target::new_fixed("StaticClass", "0x5C"),
-                Target::new_fixed("MatchStatus", "0x3C"),
-                Target::new_fixed("LocalPlayer", "0x7C"),
-                Target::new_fixed("DictionaryEntities", "0x68"),
-                Target::new_fixed("CurrentMatch", "0x50"),
-                Target::new_aob("NetworkManager_AoB", "48 8B 05 ?? ?? ?? ?? 48 0F 44 C8"),
-            ],
-        ),
-        (
-            OffsetCategory::Player,
-            vec![
-                Target::new_fixed("Player_IsDead", "0x4C"),
-                Target::new_regex("Player_Name", r"protected string [A-Z]{11}; // 0x"),
-                Target::new_regex("Player_CurHealth", r"public float [A-Z]{11}; // 0x"),
-                Target::new_regex("Player_MaxHealth", r"public float [A-Z]{11}; // 0x"),
-                Target::new_pattern(
-                    "Player_ShadowBase",
-                    "public PlayerNetwork.HHCBNAPCKHF m_ShadowState;",
-                ),
-                Target::new_fixed("XPose", "0x78"),
-                Target::new_regex("AvatarManager", r"protected AvatarManager [A-Z]{11};"),
-                Target::new_fixed("Avatar", "0x94"),
-                Target::new_fixed("Avatar_IsVisible", "0x7C"),
-                Target::new_fixed("Avatar_Data", "0x10"),
-                Target::new_fixed("Avatar_Data_IsTeam", "0x51"),
-                Target::new_regex("Player_TeamID", r"public int [A-Z]{11}; // 0x"),
-            ],
-        ),
-        (
-            OffsetCategory::Camera,
-            vec![
-                Target::new_regex("FollowCamera", r"protected FollowCamera [A-Z]{11};"),
-                Target::new_fixed("Camera", "0x14"),
-                Target::new_pattern(
-                    "AimRotation",
-                    "private Quaternion <KCFEHMAIIINO>k__BackingField;",
-                ),
-                Target::new_pattern(
-                    "MainCameraTransform",
-                    "public Transform MainCameraTransform;",
-                ),
-            ],
-        ),
-        (
-            OffsetCategory::Weapon,
-            vec![
-                Target::new_regex("Weapon", r"public [A-Z]{11} ActiveUISightingWeapon;"),
-                Target::new_fixed("WeaponData", "0x58"),
-                Target::new_fixed("WeaponRecoil", "0xC"),
-                Target::new_regex("WeaponSpread", r"public float [A-Z]{11}; // 0x"),
-                Target::new_regex("WeaponFireRate", r"public float [A-Z]{11}; // 0x"),
-                Target::new_fixed("ViewMatrix", "0x98 + 0x24"),
-            ],
-        ),
-        (
-            OffsetCategory::Silent,
-            vec![
-                Target::new_pattern("Silent1", "private bool <LPEIEILIKGC>k__BackingField;"),
-                Target::new_regex("Silent2", r"private [A-Z]{11} [A-Z]{11};"),
-                Target::new_fixed("Silent3", "0x38"),
-                Target::new_fixed("Silent4", "0x2C"),
-            ],
-        ),
-        (
-            OffsetCategory::Collision,
-            vec![Target::new_regex(
-                "HeadCollider",
-                r"protected Collider [A-Z]{11};",
-            )],
-        ),
-        (
-            OffsetCategory::Attributes,
-            vec![
-                Target::new_regex(
-                    "PlayerAttributes",
-                    r"protected PlayerAttributes [A-Z]{11};",
-                ),
-                Target::new_fixed("NoReload", "0x91"),
-                Target::new_regex("WalkSpeed", r"public float [A-Z]{11}; // 0x"),
-            ],
-        ),
-        (
-            OffsetCategory::Bot,
-            vec![Target::new_pattern("isBot", "public bool IsClientBot;")],
-        ),
-        (
-            OffsetCategory::Skeleton,
-            vec![
-                Target::new_regex("Head", r"protected ITransformNode [A-Z]{11};"),
-                Target::new_regex("Root", r"protected ITransformNode [A-Z]{11};"),
-                Target::new_regex("Spine", r"protected ITransformNode [A-Z]{11};"),
-                Target::new_regex("Hip", r"protected ITransformNode [A-Z]{11};"),
-                Target::new_regex("LeftHand", r"protected ITransformNode [A-Z]{11};"),
-                Target::new_regex("RightHand", r"protected ITransformNode [A-Z]{11};"),
-            ],
-        ),
-    ]
+// Flattens the `extends` chain into one list with a single group per
+// category.
+pub fn resolve_target_set(set: TargetSet) -> Result<TargetConfig, String> {
+    resolve_with_chain(set, &mut Vec::new())
 }
 
-fn get_freefire_max_offsets() -> Vec<(OffsetCategory, Vec<Target>)> {
-    let mut offsets = get_freefire_offsets();
-    offsets.push((
-        OffsetCategory::Core,
-        vec![
-            Target::new_fixed("MaxGraphicsSystem", "0x120"),
-            Target::new_aob("MaxRenderer_AoB", "48 89 5C 24 ?? 48 89 74 24 ?? 57 48 83 EC 20 48 8B F1 41 8B D8"),
-        ],
-    ));
-    offsets
+// `chain` holds where each parent was loaded from; sets are free to share a
+// display name, so that is not used to spot a cycle.
+fn resolve_with_chain(set: TargetSet, chain: &mut Vec<String>) -> Result<TargetConfig, String> {
+    let mut config = match &set.extends {
+        Some(parent) => {
+            let source = target_set_source(parent)?;
+            let cycle = chain.contains(&source);
+            chain.push(source);
+            if cycle {
+                return Err(format!("Target sets extend each other: {}", chain.join(" -> ")));
+            }
+            resolve_with_chain(load_named_target_set(parent)?, chain)?
+        }
+        None => Vec::new(),
+    };
+
+    for name in &set.remove {
+        let removed = config.iter_mut().any(|(_, targets)| {
+            let before = targets.len();
+            targets.retain(|t| t.name() != Some(name.as_str()));
+            targets.len() != before
+        });
+        if !removed {
+            return Err(format!("{}: cannot remove unknown target '{}'", set.name, name));
+        }
+    }
+
+    for group in set.categories {
+        for def in group.targets {
+            merge_target(&mut config, group.name, def.target);
+        }
+        // Keep categories that are declared but (so far) empty.
+        if !config.iter().any(|(category, _)| *category == group.name) {
+            config.push((group.name, Vec::new()));
+        }
+    }
+
+    Ok(config)
 }
 
-fn get_freefire_tela_offsets() -> Vec<(OffsetCategory, Vec<Target>)> {
-    let mut offsets = get_freefire_offsets();
-    
-    offsets.push((
-        OffsetCategory::Weapon,
-        vec![
-            Target::new_aob("TELA_Aimbot_Assist", "F3 0F 10 05 ?? ?? ?? ?? F3 0F 58 05 ?? ?? ?? ?? F3 0F 11 05"),
-            Target::new_aob("TELA_NoRecoil", "F3 0F 10 05 ?? ?? ?? ?? F3 0F 5C 05 ?? ?? ?? ?? 0F 28 C1"),
-            Target::new_aob("TELA_InstantHit", "40 53 48 83 EC 20 48 8B DA 0F 29 74 24 ?? 48 8B 0D ?? ?? ?? ??"),
-            Target::new_aob("TELA_WeaponSpread", "F3 0F 10 41 ?? F3 0F 58 41 ?? F3 0F 11 41 ?? C3"),
-        ],
-    ));
-
-    offsets.push((
-        OffsetCategory::Camera,
-        vec![
-            Target::new_aob("TELA_Wallhack_Chams", "48 8B 05 ?? ?? ?? ?? 48 8B 88 ?? ?? ?? ?? 80 BB ?? ?? ?? ?? ?? 74 ??"),
-            Target::new_aob("TELA_ESP_Line", "E8 ?? ?? ?? ?? 48 8B 4B ?? 48 8B 01 FF 90 ?? ?? ?? ?? 48 8D 4D ??"),
-        ],
-    ));
-
-    offsets.push((
-        OffsetCategory::Attributes,
-        vec![
-            Target::new_aob("TELA_SpeedHack", "F3 0F 10 ?? ?? ?? ?? ?? F3 0F 59 ?? F3 0F 11 ?? ?? ?? ?? ??"),
-            Target::new_aob("TELA_SkyFly", "48 8B 05 ?? ?? ?? ?? 48 8B 88 ?? ?? ?? ?? F3 0F 10 81 ?? ?? ?? ??"),
-            Target::new_aob("TELA_Underground", "F3 0F 10 81 ?? ?? ?? ?? F3 0F 5C 81 ?? ?? ?? ?? F3 0F 11 81 ?? ?? ?? ??"),
-        ],
-    ));
-
-    offsets.push((
-        OffsetCategory::Core,
-        vec![
-            Target::new_fixed("TelaSystem", "0xA0"),
-            Target::new_aob("TelaLocalPlayer", "48 8B 05 ?? ?? ?? ?? 48 8B 88 ?? ?? ?? ?? 48 85 C9 74 ?? 48 8B 01"),
-            Target::new_aob("TelaViewMatrix", "E8 ?? ?? ?? ?? 48 8D 4C 24 ?? E8 ?? ?? ?? ?? 48 8B ?? ?? ?? ?? ?? 48 8B"),
-            Target::new_aob("TelaMatchManager", "48 8B 0D ?? ?? ?? ?? 48 8B 01 48 8B 80 ?? 00 00 00 FF D0 48 8B C8"),
-        ],
-    ));
-    
-    offsets
+fn merge_target(config: &mut TargetConfig, category: OffsetCategory, target: Target) {
+    if let Some(name) = target.name() {
+        for (existing_category, targets) in config.iter_mut() {
+            if let Some(position) = targets.iter().position(|t| t.name() == Some(name)) {
+                if *existing_category == category {
+                    targets[position] = target;
+                    return;
+                }
+                targets.remove(position);
+                break;
+            }
+        }
+    }
+
+    match config.iter_mut().find(|(existing, _)| *existing == category) {
+        Some((_, targets)) => targets.push(target),
+        None => config.push((category, vec![target])),
+    }
+}
+
+// Directories searched for `--targets <name>`, in order:
+// $OFFSETS_FINDER_PROFILES, ./profiles, <exe dir>/profiles and the per-user
+// config directory.
+pub fn profile_search_path() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(paths) = env::var_os("OFFSETS_FINDER_PROFILES") {
+        dirs.extend(env::split_paths(&paths));
+    }
+    dirs.push(PathBuf::from("profiles"));
+    if let Some(exe_dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        dirs.push(exe_dir.join("profiles"));
+    }
+    if let Some(config_dir) = env::var_os("APPDATA").or_else(|| {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").into_os_string())
+    }) {
+        dirs.push(PathBuf::from(config_dir).join("offsets-finder").join("profiles"));
+    }
+
+    dirs
+}
+
+// Accepts a path to a target file or the name of one on the search path,
+// with or without its .toml/.json extension.
+pub fn find_target_file(name: &str) -> Result<PathBuf, String> {
+    let path = Path::new(name);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+
+    let search_path = profile_search_path();
+    for dir in &search_path {
+        for candidate in [name.to_string(), format!("{}.toml", name), format!("{}.json", name)] {
+            let path = dir.join(candidate);
+            if path.is_file() {
+                return Ok(path);
+            }
+        }
+    }
+
+    let searched: Vec<String> = search_path.iter().map(|d| d.display().to_string()).collect();
+    Err(format!(
+        "Target file '{}' not found (searched: {})",
+        name,
+        searched.join(", ")
+    ))
+}
+
+pub fn builtin_target_set_names() -> impl Iterator<Item = &'static str> {
+    BUILTIN_TARGETS.iter().map(|(name, _)| *name)
 }
 
 pub fn detect_game_variant(content: &str) -> Option<GameVariant> {
@@ -182,3 +191,20 @@ pub fn detect_game_variant(content: &str) -> Option<GameVariant> {
 
     None
 }
+
+// Streams text input line by line; a MAX or TELA marker anywhere wins over
+// the generic Free Fire ones, as in `detect_game_variant`.
+pub fn detect_game_variant_in(input: &Input) -> Result<Option<GameVariant>, String> {
+    let mut lines = input.lines()?;
+    let mut detected = None;
+
+    while let Some(line) = lines.next_line()? {
+        match detect_game_variant(line) {
+            Some(GameVariant::FreeFireMax) => return Ok(Some(GameVariant::FreeFireMax)),
+            Some(GameVariant::FreeFireTela) => detected = Some(GameVariant::FreeFireTela),
+            Some(variant) if detected.is_none() => detected = Some(variant),
+            _ => {}
+        }
+    }
+    Ok(detected)
+}
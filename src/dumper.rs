@@ -1,3 +1,4 @@
+use crate::input::{write_region_header, MEMORY_DUMP_MAGIC, REGION_EXECUTE, REGION_READ, REGION_WRITE};
 use sysinfo::{System};
 use std::fs::File;
 use std::io::Write;
@@ -78,7 +79,8 @@ pub fn dump_bluestacks_memory(output_path: &str) -> Result<(), String> {
 
 unsafe fn perform_dump(process_handle: HANDLE, output_path: &str) -> Result<(), String> {
     let mut file = File::create(output_path).map_err(|e| format!("Failed to create output file: {}", e))?;
-    
+    file.write_all(MEMORY_DUMP_MAGIC).map_err(|e| format!("Failed to write to file: {}", e))?;
+
     let mut address = 0;
     let mut total_dumped = 0;
 
@@ -115,6 +117,15 @@ unsafe fn perform_dump(process_handle: HANDLE, output_path: &str) -> Result<(),
             ).is_ok();
 
             if success && bytes_read > 0 {
+                let mut flags = REGION_READ;
+                if (mbi.Protect & PAGE_READWRITE).0 != 0 || (mbi.Protect & PAGE_EXECUTE_READWRITE).0 != 0 {
+                    flags |= REGION_WRITE;
+                }
+                if (mbi.Protect & PAGE_EXECUTE_READ).0 != 0 || (mbi.Protect & PAGE_EXECUTE_READWRITE).0 != 0 {
+                    flags |= REGION_EXECUTE;
+                }
+                write_region_header(&mut file, mbi.BaseAddress as u64, bytes_read as u64, flags)
+                    .map_err(|e| format!("Failed to write to file: {}", e))?;
                 file.write_all(&buffer[..bytes_read]).map_err(|e| format!("Failed to write to file: {}", e))?;
                 total_dumped += bytes_read;
             }
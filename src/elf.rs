@@ -0,0 +1,272 @@
+// Just enough of the ELF format to map libil2cpp.so: program headers for the
+// loaded segments, section headers for named ranges like .text, and the
+// relative relocations that fill in pointers when the library is loaded.
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const DT_RELA: u64 = 7;
+const DT_RELASZ: u64 = 8;
+const DT_RELAENT: u64 = 9;
+const EM_386: u16 = 3;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+const PF_X: u32 = 0x1;
+const SHT_NOBITS: u32 = 8;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+
+#[derive(Debug, Clone)]
+pub struct ElfFile {
+    pub is_64: bool,
+    pub segments: Vec<ElfSegment>,
+    pub sections: Vec<ElfSection>,
+    // (address, value) of each RELA relative relocation. On AArch64 the
+    // pointer slots themselves are zero in the file; the value only exists
+    // here. REL relocations keep it in place and need no entry.
+    pub relocations: Vec<(u64, u64)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ElfSegment {
+    pub kind: u32,
+    pub flags: u32,
+    pub offset: u64,
+    pub vaddr: u64,
+    pub file_size: u64,
+}
+
+impl ElfSegment {
+    pub fn is_load(&self) -> bool {
+        self.kind == PT_LOAD
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.flags & PF_X != 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ElfSection {
+    pub name: String,
+    pub kind: u32,
+    pub flags: u64,
+    pub addr: u64,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl ElfSection {
+    // Sections that are mapped at runtime and have bytes in the file.
+    pub fn is_loaded(&self) -> bool {
+        self.flags & SHF_ALLOC != 0 && self.kind != SHT_NOBITS && self.size > 0
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.flags & SHF_EXECINSTR != 0
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl Reader<'_> {
+    fn slice<const N: usize>(&self, offset: u64) -> Result<[u8; N], String> {
+        usize::try_from(offset)
+            .ok()
+            .and_then(|start| self.bytes.get(start..start.checked_add(N)?))
+            .map(|bytes| bytes.try_into().unwrap())
+            .ok_or_else(|| format!("ELF truncated at byte 0x{:X}", offset))
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16, String> {
+        let bytes = self.slice(offset)?;
+        Ok(if self.big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) })
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, String> {
+        let bytes = self.slice(offset)?;
+        Ok(if self.big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64, String> {
+        let bytes = self.slice(offset)?;
+        Ok(if self.big_endian { u64::from_be_bytes(bytes) } else { u64::from_le_bytes(bytes) })
+    }
+
+    // Address-sized field: 8 bytes in ELF64, 4 in ELF32.
+    fn word(&self, offset: u64, is_64: bool) -> Result<u64, String> {
+        if is_64 {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+}
+
+pub fn parse_elf(bytes: &[u8]) -> Result<ElfFile, String> {
+    if !bytes.starts_with(b"\x7fELF") || bytes.len() < 0x34 {
+        return Err("not an ELF file".to_string());
+    }
+    let is_64 = match bytes[4] {
+        1 => false,
+        2 => true,
+        class => return Err(format!("unknown ELF class {}", class)),
+    };
+    let big_endian = match bytes[5] {
+        1 => false,
+        2 => true,
+        data => return Err(format!("unknown ELF data encoding {}", data)),
+    };
+    let reader = Reader { bytes, big_endian };
+
+    let (phoff, shoff, sizes) = if is_64 {
+        (reader.u64(0x20)?, reader.u64(0x28)?, 0x36)
+    } else {
+        (reader.u32(0x1C)? as u64, reader.u32(0x20)? as u64, 0x2A)
+    };
+    let phentsize = reader.u16(sizes)? as u64;
+    let phnum = reader.u16(sizes + 2)? as u64;
+    let shentsize = reader.u16(sizes + 4)? as u64;
+    let shnum = reader.u16(sizes + 6)? as u64;
+    let shstrndx = reader.u16(sizes + 8)? as u64;
+
+    let mut segments = Vec::new();
+    for i in 0..phnum {
+        let base = phoff + i * phentsize;
+        segments.push(if is_64 {
+            ElfSegment {
+                kind: reader.u32(base)?,
+                flags: reader.u32(base + 0x4)?,
+                offset: reader.u64(base + 0x8)?,
+                vaddr: reader.u64(base + 0x10)?,
+                file_size: reader.u64(base + 0x20)?,
+            }
+        } else {
+            ElfSegment {
+                kind: reader.u32(base)?,
+                offset: reader.u32(base + 0x4)? as u64,
+                vaddr: reader.u32(base + 0x8)? as u64,
+                file_size: reader.u32(base + 0x10)? as u64,
+                flags: reader.u32(base + 0x18)?,
+            }
+        });
+    }
+
+    // Stripped or packed libraries may carry no (or bogus) section headers;
+    // segments are enough to scan, so treat those as "no sections".
+    let sections = if shoff != 0 && shnum != 0 {
+        parse_sections(&reader, is_64, shoff, shentsize, shnum, shstrndx).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let machine = reader.u16(0x12)?;
+    let relocations = parse_relocations(&reader, is_64, machine, &segments).unwrap_or_default();
+
+    Ok(ElfFile {
+        is_64,
+        segments,
+        sections,
+        relocations,
+    })
+}
+
+// Maps a virtual address to its file offset through the loaded segments.
+fn file_offset(segments: &[ElfSegment], address: u64) -> Option<u64> {
+    segments
+        .iter()
+        .filter(|segment| segment.is_load())
+        .find(|segment| address >= segment.vaddr && address - segment.vaddr < segment.file_size)
+        .map(|segment| segment.offset + (address - segment.vaddr))
+}
+
+fn parse_relocations(
+    reader: &Reader,
+    is_64: bool,
+    machine: u16,
+    segments: &[ElfSegment],
+) -> Result<Vec<(u64, u64)>, String> {
+    let relative = match machine {
+        EM_AARCH64 => 1027,
+        EM_X86_64 | EM_386 => 8,
+        _ => return Ok(Vec::new()),
+    };
+    let Some(dynamic) = segments.iter().find(|segment| segment.kind == PT_DYNAMIC) else {
+        return Ok(Vec::new());
+    };
+
+    let word = if is_64 { 8 } else { 4 };
+    let (mut table, mut size, mut entry_size) = (None, 0, 3 * word);
+    for i in 0..dynamic.file_size / (2 * word) {
+        let at = dynamic.offset + i * 2 * word;
+        let (tag, value) = (reader.word(at, is_64)?, reader.word(at + word, is_64)?);
+        match tag {
+            0 => break,
+            DT_RELA => table = Some(value),
+            DT_RELASZ => size = value,
+            DT_RELAENT => entry_size = value.max(3 * word),
+            _ => {}
+        }
+    }
+    let Some(start) = table.and_then(|address| file_offset(segments, address)) else {
+        return Ok(Vec::new());
+    };
+
+    let mut relocations = Vec::new();
+    for i in 0..size / entry_size {
+        let at = start + i * entry_size;
+        let info = reader.word(at + word, is_64)?;
+        let kind = if is_64 { info & 0xFFFF_FFFF } else { info & 0xFF };
+        if kind == relative {
+            relocations.push((reader.word(at, is_64)?, reader.word(at + 2 * word, is_64)?));
+        }
+    }
+    Ok(relocations)
+}
+
+fn parse_sections(
+    reader: &Reader,
+    is_64: bool,
+    shoff: u64,
+    shentsize: u64,
+    shnum: u64,
+    shstrndx: u64,
+) -> Result<Vec<ElfSection>, String> {
+    let mut sections = Vec::new();
+    let mut name_offsets = Vec::new();
+    for i in 0..shnum {
+        let base = shoff + i * shentsize;
+        let (flags, addr, offset, size) = (
+            reader.word(base + 0x8, is_64)?,
+            reader.word(base + if is_64 { 0x10 } else { 0xC }, is_64)?,
+            reader.word(base + if is_64 { 0x18 } else { 0x10 }, is_64)?,
+            reader.word(base + if is_64 { 0x20 } else { 0x14 }, is_64)?,
+        );
+        name_offsets.push(reader.u32(base)?);
+        sections.push(ElfSection {
+            name: String::new(),
+            kind: reader.u32(base + 0x4)?,
+            flags,
+            addr,
+            offset,
+            size,
+        });
+    }
+
+    if let Some(strtab) = sections.get(shstrndx as usize).cloned() {
+        for (section, name_offset) in sections.iter_mut().zip(name_offsets) {
+            section.name = read_name(reader.bytes, strtab.offset + name_offset as u64);
+        }
+    }
+    Ok(sections)
+}
+
+fn read_name(bytes: &[u8], offset: u64) -> String {
+    let Some(tail) = usize::try_from(offset).ok().and_then(|start| bytes.get(start..)) else {
+        return String::new();
+    };
+    let end = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+    String::from_utf8_lossy(&tail[..end]).into_owned()
+}
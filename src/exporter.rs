@@ -1,4 +1,4 @@
-use crate::models::{ExportFormat, GameVariant, OffsetResult};
+use crate::models::{Candidate, ExportFormat, GameVariant, OffsetResult, Provenance};
 use serde_json::json;
 use std::collections::HashMap;
 use std::fs;
@@ -20,6 +20,14 @@ pub fn export_results(
         .map_err(|e| format!("Failed to write export file: {}", e))
 }
 
+pub fn cpp_const_name(name: &str) -> String {
+    name.to_uppercase().replace(' ', "_")
+}
+
+pub fn rust_const_name(name: &str) -> String {
+    name.to_uppercase().replace([' ', '-'], "_")
+}
+
 fn export_json(results: &[OffsetResult], game: GameVariant) -> String {
     let mut offsets_by_category: HashMap<String, HashMap<String, String>> = HashMap::new();
 
@@ -27,19 +35,44 @@ fn export_json(results: &[OffsetResult], game: GameVariant) -> String {
         if let Some(offset) = &result.offset {
             offsets_by_category
                 .entry(result.category.clone())
-                .or_insert_with(HashMap::new)
-                .insert(result.name.clone(), offset.clone());
+                .or_default()
+                .insert(result.name.clone(), offset.hex());
         }
     }
 
+    let provenance: HashMap<&str, &Provenance> = results
+        .iter()
+        .filter(|r| r.offset.is_some())
+        .map(|r| (r.name.as_str(), &r.provenance))
+        .collect();
+
+    let ambiguous: HashMap<&str, &[Candidate]> = results
+        .iter()
+        .filter(|r| r.ambiguous)
+        .map(|r| (r.name.as_str(), r.candidates.as_slice()))
+        .collect();
+
+    let failed: HashMap<&str, serde_json::Value> = results
+        .iter()
+        .filter_map(|r| {
+            let error = r.error.as_ref()?;
+            Some((r.name.as_str(), json!({ "error": error, "candidates": r.candidates })))
+        })
+        .collect();
+
     let output = json!({
         "game": game.name(),
         "timestamp": chrono::Local::now().to_rfc3339(),
         "offsets": offsets_by_category,
+        "provenance": provenance,
+        "ambiguous": ambiguous,
+        "failed": failed,
         "statistics": {
             "total": results.len(),
             "found": results.iter().filter(|r| r.offset.is_some()).count(),
             "missing": results.iter().filter(|r| r.offset.is_none()).count(),
+            "ambiguous": ambiguous.len(),
+            "invalid": failed.len(),
         }
     });
 
@@ -65,17 +98,18 @@ fn export_cpp_header(results: &[OffsetResult], game: GameVariant) -> String {
     for result in results {
         if result.category != current_category {
             if !current_category.is_empty() {
-                output.push_str("\n");
+                output.push('\n');
             }
             output.push_str(&format!("    // {}\n", result.category));
             current_category = result.category.clone();
         }
 
         if let Some(offset) = &result.offset {
-            let const_name = result.name.to_uppercase().replace(" ", "_");
             output.push_str(&format!(
-                "    constexpr uintptr_t {} = {};\n",
-                const_name, offset
+                "    constexpr uintptr_t {} = {}; // {}\n",
+                cpp_const_name(&result.name),
+                offset.hex(),
+                result.provenance.summary()
             ));
         }
     }
@@ -102,28 +136,18 @@ fn export_rust_module(results: &[OffsetResult], game: GameVariant) -> String {
     for result in results {
         if result.category != current_category {
             if !current_category.is_empty() {
-                output.push_str("\n");
+                output.push('\n');
             }
             output.push_str(&format!("    // {}\n", result.category));
             current_category = result.category.clone();
         }
 
         if let Some(offset) = &result.offset {
-            let const_name = result
-                .name
-                .to_uppercase()
-                .replace(" ", "_")
-                .replace("-", "_");
-            
-            let hex_value = if offset.starts_with("0x") {
-                offset.to_string()
-            } else {
-                format!("0x{}", offset)
-            };
-            
             output.push_str(&format!(
-                "    pub const {}: usize = {};\n",
-                const_name, hex_value
+                "    pub const {}: usize = {}; // {}\n",
+                rust_const_name(&result.name),
+                offset.hex(),
+                result.provenance.summary()
             ));
         }
     }
@@ -140,21 +164,29 @@ fn export_plain_text(results: &[OffsetResult], game: GameVariant) -> String {
     for result in results {
         if result.category != current_category {
             if !current_category.is_empty() {
-                output.push_str("\n");
+                output.push('\n');
             }
             output.push_str(&format!("--- {} ---\n", result.category));
             current_category = result.category.clone();
         }
 
         match &result.offset {
-            Some(offset) => output.push_str(&format!("{} = {}\n", result.name, offset)),
-            None => output.push_str(&format!("{} = NOT FOUND\n", result.name)),
+            Some(offset) => output.push_str(&format!(
+                "{} = {}  ({})\n",
+                result.name,
+                offset,
+                result.provenance.summary()
+            )),
+            None => match &result.error {
+                Some(error) => output.push_str(&format!("{} = INVALID ({})\n", result.name, error)),
+                None => output.push_str(&format!("{} = NOT FOUND\n", result.name)),
+            },
         }
     }
 
     let found = results.iter().filter(|r| r.offset.is_some()).count();
     let total = results.len();
-    output.push_str(&format!("\n====== STATISTICS ======\n"));
+    output.push_str("\n====== STATISTICS ======\n");
     output.push_str(&format!("Found: {}/{}\n", found, total));
 
     output
@@ -0,0 +1,173 @@
+use crate::models::{OffsetResult, OffsetValue};
+use std::collections::HashMap;
+
+// Offset expressions: sums and differences of constants and references to
+// other targets, e.g. "${Camera} + 0x24" or "0x98 + 0x24". As in `hex`,
+// hex needs the 0x prefix; bare numbers are decimal.
+#[derive(Debug, Clone)]
+pub struct Expr {
+    terms: Vec<(bool, Term)>,
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Const(u64),
+    Ref(String),
+}
+
+impl Expr {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+        let mut rest = text.trim();
+        let mut negative = false;
+        let mut expect_term = true;
+
+        while !rest.is_empty() {
+            if expect_term {
+                let (term, tail) = parse_term(rest)
+                    .ok_or_else(|| format!("expected a number or ${{Target}} at '{}'", rest))?;
+                terms.push((negative, term));
+                rest = tail.trim_start();
+                expect_term = false;
+            } else {
+                negative = match rest.as_bytes()[0] {
+                    b'+' => false,
+                    b'-' => true,
+                    _ => return Err(format!("expected '+' or '-' at '{}'", rest)),
+                };
+                rest = rest[1..].trim_start();
+                expect_term = true;
+            }
+        }
+
+        if terms.is_empty() || expect_term {
+            return Err(format!("incomplete expression '{}'", text));
+        }
+        Ok(Expr { terms })
+    }
+
+    pub fn references(&self) -> impl Iterator<Item = &str> {
+        self.terms.iter().filter_map(|(_, term)| match term {
+            Term::Ref(name) => Some(name.as_str()),
+            Term::Const(_) => None,
+        })
+    }
+
+    pub fn eval(&self, mut lookup: impl FnMut(&str) -> Result<u64, String>) -> Result<u64, String> {
+        let mut total: i128 = 0;
+        for (negative, term) in &self.terms {
+            let value = match term {
+                Term::Const(value) => *value as i128,
+                Term::Ref(name) => lookup(name)? as i128,
+            };
+            total += if *negative { -value } else { value };
+        }
+        u64::try_from(total).map_err(|_| format!("expression evaluates to {}", total))
+    }
+}
+
+fn parse_term(text: &str) -> Option<(Term, &str)> {
+    if let Some(inner) = text.strip_prefix("${") {
+        let end = inner.find('}')?;
+        let name = inner[..end].trim();
+        if name.is_empty() {
+            return None;
+        }
+        return Some((Term::Ref(name.to_string()), &inner[end + 1..]));
+    }
+
+    let end = text
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(text.len());
+    let literal = &text[..end];
+    let value = if literal.starts_with("0x") || literal.starts_with("0X") {
+        OffsetValue::parse(literal).ok()?.value
+    } else {
+        literal.parse().ok()?
+    };
+    Some((Term::Const(value), &text[end..]))
+}
+
+// Evaluates expression targets once every base target has been resolved.
+// `pending` pairs a result index with its expression source.
+pub fn resolve_expressions(results: &mut [OffsetResult], pending: &[(usize, String)]) {
+    let index_by_name: HashMap<String, usize> = results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.name.clone(), i))
+        .collect();
+
+    let mut resolver = Resolver {
+        results,
+        index_by_name,
+        exprs: HashMap::new(),
+        done: HashMap::new(),
+        stack: Vec::new(),
+    };
+
+    for (index, source) in pending {
+        let expr = Expr::parse(source);
+        resolver.exprs.insert(*index, expr);
+    }
+
+    for (index, _) in pending {
+        let outcome = resolver.resolve(*index);
+        let result = &mut resolver.results[*index];
+        match outcome {
+            Ok(value) => result.offset = Some(OffsetValue::new(value)),
+            Err(e) => result.error = Some(e),
+        }
+    }
+}
+
+struct Resolver<'a> {
+    results: &'a mut [OffsetResult],
+    index_by_name: HashMap<String, usize>,
+    exprs: HashMap<usize, Result<Expr, String>>,
+    done: HashMap<usize, Result<u64, String>>,
+    stack: Vec<usize>,
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, index: usize) -> Result<u64, String> {
+        if let Some(outcome) = self.done.get(&index) {
+            return outcome.clone();
+        }
+
+        let expr = match self.exprs.get(&index) {
+            Some(Ok(expr)) => expr.clone(),
+            Some(Err(e)) => return Err(e.clone()),
+            None => {
+                let result = &self.results[index];
+                return match (&result.offset, &result.error) {
+                    (Some(offset), _) => Ok(offset.value),
+                    (None, Some(error)) => Err(error.clone()),
+                    (None, None) => Err("not found".to_string()),
+                };
+            }
+        };
+
+        if let Some(start) = self.stack.iter().position(|&i| i == index) {
+            let mut cycle: Vec<&str> = self.stack[start..]
+                .iter()
+                .map(|&i| self.results[i].name.as_str())
+                .collect();
+            cycle.push(&self.results[index].name);
+            return Err(format!("dependency cycle: {}", cycle.join(" -> ")));
+        }
+
+        self.stack.push(index);
+        let outcome = expr.eval(|name| {
+            let dependency = *self
+                .index_by_name
+                .get(name)
+                .ok_or_else(|| format!("unknown target '{}'", name))?;
+            self.resolve(dependency)
+                .map_err(|e| format!("depends on '{}': {}", name, e))
+        });
+        self.stack.pop();
+
+        self.done.insert(index, outcome.clone());
+        outcome
+    }
+}
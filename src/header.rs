@@ -0,0 +1,313 @@
+// Reader for Il2CppDumper's il2cpp.h. A managed type `Game.Player` shows up
+// as `Game_Player_Fields` (its instance fields, deriving from the parent's
+// _Fields), `Game_Player_StaticFields` and `Game_Player_o` (the object
+// header followed by `fields`). Field offsets are computed from those C
+// layouts; classes keep their C names since the '.' to '_' mapping loses
+// where the namespace ends. The runtime's own structs (Il2CppClass,
+// Il2CppObject, ...) become classes too, so offsets such as
+// Il2CppClass::static_fields can be read for the header's bitness.
+
+use crate::input::TextLines;
+use crate::models::{ClassDef, FieldDef, Location, TypeKind};
+use regex::Regex;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+// Guards against layouts that (erroneously) contain themselves.
+const MAX_NESTING: usize = 32;
+
+struct CStruct {
+    base: Option<String>,
+    members: Vec<CMember>,
+    line: usize,
+}
+
+struct CMember {
+    type_name: String,
+    name: String,
+    count: u64,
+    line: usize,
+}
+
+struct Layout {
+    // Per member; None from the first member whose size is unknown.
+    offsets: Vec<Option<u64>>,
+    size: Option<u64>,
+    align: u64,
+}
+
+pub fn parse_header<R: BufRead>(lines: &mut TextLines<R>) -> Result<Vec<ClassDef>, String> {
+    let open_re =
+        Regex::new(r"^(?:typedef\s+)?struct\s+(\w+)\s*(?::\s*(\w+)\s*)?(?:\w+\s*\(\s*\d+\s*\)\s*)?\{?$").unwrap();
+    let member_re = Regex::new(r"^(.+?)\s*\b(\w+)\s*(?:\[(\d+)\])?\s*;$").unwrap();
+
+    let mut structs: HashMap<String, CStruct> = HashMap::new();
+    let mut order = Vec::new();
+    let mut pointer_size = 8;
+    let mut current: Option<(String, CStruct)> = None;
+    // Depth inside unions and anonymous structs of the current struct.
+    let mut nested = 0;
+    let mut line_number = 0;
+
+    while let Some(line) = lines.next_line()? {
+        line_number += 1;
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if line == "#define IS_32BIT" {
+            pointer_size = 4;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((_, definition)) = current.as_mut() else {
+            if let Some(captures) = open_re.captures(line) {
+                let name = captures[1].to_string();
+                let wanted = ["_Fields", "_StaticFields", "_o"].iter().any(|suffix| name.ends_with(suffix))
+                    || is_runtime_struct(&name);
+                if wanted {
+                    let base = captures.get(2).map(|base| base.as_str().to_string());
+                    current = Some((name, CStruct { base, members: Vec::new(), line: line_number }));
+                }
+            }
+            continue;
+        };
+
+        if line == "{" && definition.members.is_empty() {
+            // The brace on its own line after `struct X`.
+        } else if nested > 0 {
+            nested += line.matches('{').count();
+            nested -= line.matches('}').count().min(nested);
+        } else if line.starts_with('}') {
+            let (name, definition) = current.take().unwrap();
+            order.push(name.clone());
+            structs.insert(name, definition);
+        } else if line.ends_with('{') {
+            // A union or inline struct: its size is not worked out, so
+            // members from here on get no offset.
+            nested = 1;
+            definition.members.push(CMember {
+                type_name: String::new(),
+                name: String::new(),
+                count: 1,
+                line: line_number,
+            });
+        } else if let Some(captures) = member_re.captures(line) {
+            definition.members.push(CMember {
+                type_name: captures[1].trim().to_string(),
+                name: captures[2].to_string(),
+                count: captures.get(3).map_or(1, |count| count.as_str().parse().unwrap_or(1)),
+                line: line_number,
+            });
+        }
+    }
+
+    let header = Header {
+        structs,
+        pointer_size,
+        sizes: RefCell::new(HashMap::new()),
+    };
+    Ok(header.classes(&order))
+}
+
+struct Header {
+    structs: HashMap<String, CStruct>,
+    pointer_size: u64,
+    sizes: RefCell<HashMap<String, Option<(u64, u64)>>>,
+}
+
+impl Header {
+    fn classes(&self, order: &[String]) -> Vec<ClassDef> {
+        let mut classes: Vec<ClassDef> = Vec::new();
+        let mut index: HashMap<&str, usize> = HashMap::new();
+
+        for struct_name in order {
+            let (name, is_static) = if let Some(name) = struct_name.strip_suffix("_StaticFields") {
+                (name, true)
+            } else if let Some(name) = struct_name.strip_suffix("_Fields") {
+                (name, false)
+            } else if is_runtime_struct(struct_name) && !struct_name.ends_with("_o") {
+                classes.push(self.runtime_class(struct_name));
+                continue;
+            } else {
+                continue;
+            };
+            let definition = &self.structs[struct_name];
+            let class = match index.get(name) {
+                Some(&i) => &mut classes[i],
+                None => {
+                    index.insert(name, classes.len());
+                    classes.push(self.class(name, definition.line));
+                    classes.last_mut().unwrap()
+                }
+            };
+
+            // Instance offsets count from the object, so add where `fields`
+            // sits in X_o (past klass and monitor for reference types).
+            let start = if is_static {
+                Some(0)
+            } else {
+                class.parent = definition.base.as_deref().map(|base| {
+                    base.strip_suffix("_Fields").unwrap_or(base).to_string()
+                });
+                match self.structs.get(&format!("{}_o", name)) {
+                    Some(object) => object
+                        .members
+                        .iter()
+                        .position(|member| member.name == "fields")
+                        .and_then(|i| self.layout(object, 0).offsets[i]),
+                    None => Some(0),
+                }
+            };
+
+            let layout = self.layout(definition, 0);
+            for (member, offset) in definition.members.iter().zip(layout.offsets) {
+                if member.name.is_empty() {
+                    continue;
+                }
+                let offset = start.zip(offset).map(|(start, offset)| start + offset);
+                class.fields.push(field(member, offset, is_static));
+            }
+        }
+        classes
+    }
+
+    fn class(&self, name: &str, line: usize) -> ClassDef {
+        let kind = match self.structs.get(&format!("{}_o", name)) {
+            Some(object) if !object.members.iter().any(|member| member.name == "klass") => {
+                TypeKind::Struct
+            }
+            _ => TypeKind::Class,
+        };
+        ClassDef {
+            namespace: String::new(),
+            name: name.to_string(),
+            kind,
+            modifiers: Vec::new(),
+            parent: None,
+            interfaces: Vec::new(),
+            type_def_index: None,
+            location: Location::Line(line),
+            fields: Vec::new(),
+            properties: Vec::new(),
+            methods: Vec::new(),
+        }
+    }
+
+    fn runtime_class(&self, struct_name: &str) -> ClassDef {
+        let definition = &self.structs[struct_name];
+        let mut class = self.class(struct_name, definition.line);
+        class.kind = TypeKind::Struct;
+        let layout = self.layout(definition, 0);
+        for (member, offset) in definition.members.iter().zip(layout.offsets) {
+            if !member.name.is_empty() {
+                class.fields.push(field(member, offset, false));
+            }
+        }
+        class
+    }
+
+    // C layout: each member at the next multiple of its alignment, derived
+    // members after the base, the size padded to the largest alignment.
+    fn layout(&self, definition: &CStruct, depth: usize) -> Layout {
+        let unknown = |count| Layout { offsets: vec![None; count], size: None, align: 1 };
+        let (mut offset, mut align) = match &definition.base {
+            Some(base) => match self.size_of(base, depth + 1) {
+                Some(size_align) => size_align,
+                None => return unknown(definition.members.len()),
+            },
+            None => (0, 1),
+        };
+
+        let mut offsets = Vec::new();
+        let mut known = true;
+        for member in &definition.members {
+            let size_align = known
+                .then(|| self.type_size(&member.type_name, depth + 1))
+                .flatten();
+            match size_align {
+                Some((size, member_align)) => {
+                    offset = offset.next_multiple_of(member_align);
+                    offsets.push(Some(offset));
+                    offset += size * member.count;
+                    align = align.max(member_align);
+                }
+                None => {
+                    known = false;
+                    offsets.push(None);
+                }
+            }
+        }
+        Layout {
+            offsets,
+            size: known.then(|| offset.next_multiple_of(align)),
+            align,
+        }
+    }
+
+    fn size_of(&self, struct_name: &str, depth: usize) -> Option<(u64, u64)> {
+        if let Some(&known) = self.sizes.borrow().get(struct_name) {
+            return known;
+        }
+        let size = match self.structs.get(struct_name) {
+            Some(definition) if depth < MAX_NESTING => {
+                let layout = self.layout(definition, depth);
+                layout.size.map(|size| (size, layout.align))
+            }
+            _ => None,
+        };
+        self.sizes.borrow_mut().insert(struct_name.to_string(), size);
+        size
+    }
+
+    fn type_size(&self, type_name: &str, depth: usize) -> Option<(u64, u64)> {
+        if type_name.ends_with('*') {
+            return Some((self.pointer_size, self.pointer_size));
+        }
+        let name = type_name
+            .split_whitespace()
+            .rfind(|word| !matches!(*word, "struct" | "const" | "volatile" | "unsigned"))
+            .unwrap_or_default();
+        let size = match name {
+            "bool" | "char" | "int8_t" | "uint8_t" => 1,
+            "int16_t" | "uint16_t" | "Il2CppChar" | "short" => 2,
+            "int32_t" | "uint32_t" | "float" | "int" => 4,
+            "int64_t" | "uint64_t" | "double" => 8,
+            "intptr_t" | "uintptr_t" | "size_t" | "il2cpp_array_size_t" | "il2cpp_array_lower_bound_t" => {
+                self.pointer_size
+            }
+            "" => return None,
+            name => return self.size_of(name, depth),
+        };
+        Some((size, size))
+    }
+}
+
+// Il2CppClass is split into Il2CppClass_1/_2 around static_fields, and
+// VirtualInvokeData fills its vtable; both are needed for its layout.
+fn is_runtime_struct(name: &str) -> bool {
+    name.starts_with("Il2Cpp") || name == "VirtualInvokeData"
+}
+
+fn field(member: &CMember, offset: Option<u64>, is_static: bool) -> FieldDef {
+    let modifiers = if is_static { vec!["static".to_string()] } else { Vec::new() };
+    let array = if member.count > 1 { format!("[{}]", member.count) } else { String::new() };
+    let mut text = format!(
+        "\t{}{} {}{};",
+        if is_static { "static " } else { "" },
+        member.type_name,
+        member.name,
+        array
+    );
+    if let Some(offset) = offset {
+        text.push_str(&format!(" // 0x{:X}", offset));
+    }
+    FieldDef {
+        modifiers,
+        type_name: member.type_name.clone(),
+        name: member.name.clone(),
+        offset,
+        location: Location::Line(member.line),
+        text,
+    }
+}
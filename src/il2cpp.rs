@@ -0,0 +1,622 @@
+// Pairs global-metadata.dat with the libil2cpp binary it was built with and
+// turns the two into the class model dump.cs parsing produces, so targets
+// work the same on either. The binary's Il2CppMetadataRegistration supplies
+// type signatures and field offsets, and each image's Il2CppCodeGenModule
+// the method addresses. Symbols are stripped, so both are found by shape.
+
+use crate::metadata::{Metadata, Revision, TypeDefinition};
+use crate::models::{ClassDef, DumpModel, FieldDef, Location, MethodDef, ParamDef, TypeKind};
+use crate::scanner::BinaryImage;
+use memchr::memmem;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// Where the binary sits relative to the metadata, in an extracted APK
+// (assets/bin/Data/Managed/Metadata/) or a Windows build.
+const BINARY_CANDIDATES: &[&str] = &[
+    "libil2cpp.so",
+    "GameAssembly.dll",
+    "lib/arm64-v8a/libil2cpp.so",
+    "lib/armeabi-v7a/libil2cpp.so",
+    "lib/x86_64/libil2cpp.so",
+    "lib/x86/libil2cpp.so",
+];
+
+// Il2CppTypeEnum values that need more than a name lookup.
+const TYPE_PTR: u8 = 0x0F;
+const TYPE_VALUETYPE: u8 = 0x11;
+const TYPE_CLASS: u8 = 0x12;
+const TYPE_VAR: u8 = 0x13;
+const TYPE_ARRAY: u8 = 0x14;
+const TYPE_GENERICINST: u8 = 0x15;
+const TYPE_SZARRAY: u8 = 0x1D;
+const TYPE_MVAR: u8 = 0x1E;
+
+const TYPE_ATTRIBUTE_INTERFACE: u32 = 0x20;
+const TYPE_ATTRIBUTE_ABSTRACT: u32 = 0x80;
+const TYPE_ATTRIBUTE_SEALED: u32 = 0x100;
+const FIELD_ATTRIBUTE_STATIC: u16 = 0x10;
+const FIELD_ATTRIBUTE_INIT_ONLY: u16 = 0x20;
+const FIELD_ATTRIBUTE_LITERAL: u16 = 0x40;
+const METHOD_ATTRIBUTE_STATIC: u16 = 0x10;
+const METHOD_ATTRIBUTE_FINAL: u16 = 0x20;
+const METHOD_ATTRIBUTE_VIRTUAL: u16 = 0x40;
+const METHOD_ATTRIBUTE_NEW_SLOT: u16 = 0x100;
+const METHOD_ATTRIBUTE_ABSTRACT: u16 = 0x400;
+const METHOD_ATTRIBUTE_PINVOKE_IMPL: u16 = 0x2000;
+
+// Nesting limit for type signatures; real ones are shallow, so anything
+// deeper is a misread.
+const MAX_TYPE_DEPTH: usize = 16;
+
+pub fn find_binary(metadata_path: &Path) -> Option<PathBuf> {
+    metadata_path.ancestors().skip(1).find_map(|dir| {
+        BINARY_CANDIDATES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+pub fn build_model(metadata: &Metadata, image: &BinaryImage) -> Result<DumpModel, String> {
+    let context = Context::new(metadata, image)?;
+    let method_addresses = context.method_addresses();
+    let mut model = DumpModel::default();
+
+    for (index, definition) in metadata.types.iter().enumerate() {
+        let mut class = context.class(index, definition);
+        for i in 0..definition.field_count as usize {
+            class.fields.push(context.field(index, definition, i));
+        }
+        for i in 0..definition.method_count as usize {
+            let method = definition.method_start as usize + i;
+            let address = method_addresses.get(&method).copied();
+            class.methods.push(context.method(method, address));
+        }
+        model.classes.push(class);
+    }
+
+    Ok(model)
+}
+
+struct Context<'m, 'b> {
+    metadata: &'m Metadata<'m>,
+    image: &'b BinaryImage<'b>,
+    pointer_size: u64,
+    types: u64,
+    types_count: u64,
+    field_offsets: u64,
+    names: RefCell<HashMap<u64, String>>,
+}
+
+impl<'m, 'b> Context<'m, 'b> {
+    fn new(metadata: &'m Metadata, image: &'b BinaryImage) -> Result<Self, String> {
+        let pointer_size = image.pointer_size as u64;
+        let registration = find_metadata_registration(metadata, image).ok_or_else(|| {
+            format!(
+                "could not find Il2CppMetadataRegistration for metadata v{} ({} layout); \
+                 is this the binary the metadata was built with?",
+                metadata.version,
+                metadata.revision.name()
+            )
+        })?;
+        let word = |index: u64| image.read_value(registration + index * pointer_size, image.pointer_size);
+
+        Ok(Context {
+            metadata,
+            image,
+            pointer_size,
+            types_count: word(6).unwrap_or(0),
+            types: image.read_pointer(registration + 7 * pointer_size)?,
+            field_offsets: image.read_pointer(registration + 11 * pointer_size)?,
+            names: RefCell::new(HashMap::new()),
+        })
+    }
+
+    fn pointer(&self, address: u64) -> Option<u64> {
+        self.image.read_pointer(address).ok().filter(|&pointer| pointer != 0)
+    }
+
+    // The pointer in slot `index` of the array at `base`. Bases come from the
+    // binary, so the address is checked rather than trusted not to overflow.
+    fn pointer_at(&self, base: u64, index: u64) -> Option<u64> {
+        self.pointer(base.checked_add(index.checked_mul(self.pointer_size)?)?)
+    }
+
+    // Address of the Il2CppType at `index` in the binary's type table.
+    fn type_address(&self, index: i32) -> Option<u64> {
+        let index = u64::try_from(index).ok().filter(|&index| index < self.types_count)?;
+        self.pointer_at(self.types, index)
+    }
+
+    // The Il2CppType's data word and its attrs/type bitfield.
+    fn type_parts(&self, address: u64) -> Option<(u64, u16, u8)> {
+        let data = self.image.read_value(address, self.image.pointer_size)?;
+        let bits = self.image.read_value(address.checked_add(self.pointer_size)?, 4)? as u32;
+        Some((data, bits as u16, (bits >> 16) as u8))
+    }
+
+    fn type_attributes(&self, index: i32) -> u16 {
+        self.type_address(index)
+            .and_then(|address| self.type_parts(address))
+            .map_or(0, |(_, attributes, _)| attributes)
+    }
+
+    fn type_name_at(&self, index: i32) -> String {
+        match self.type_address(index) {
+            Some(address) => self.type_name(address, 0),
+            None => "?".to_string(),
+        }
+    }
+
+    // The C# spelling Il2CppDumper uses in dump.cs.
+    fn type_name(&self, address: u64, depth: usize) -> String {
+        if let Some(name) = self.names.borrow().get(&address) {
+            return name.clone();
+        }
+        let Some((data, _, kind)) = self.type_parts(address).filter(|_| depth < MAX_TYPE_DEPTH) else {
+            return "?".to_string();
+        };
+        let nested = |pointer: Option<u64>| match pointer {
+            Some(pointer) => self.type_name(pointer, depth + 1),
+            None => "?".to_string(),
+        };
+
+        let name = match kind {
+            TYPE_CLASS | TYPE_VALUETYPE => self.definition_name(data, true, depth),
+            TYPE_PTR => format!("{}*", nested(self.pointer(address))),
+            TYPE_SZARRAY => format!("{}[]", nested(self.pointer(address))),
+            TYPE_ARRAY => {
+                // Il2CppArrayType: element type pointer, then the rank.
+                let array = self.pointer(address);
+                let element = array.and_then(|array| self.pointer(array));
+                let rank = array
+                    .and_then(|array| self.image.read_value(array.checked_add(self.pointer_size)?, 1))
+                    .unwrap_or(1);
+                format!("{}[{}]", nested(element), ",".repeat(rank.saturating_sub(1) as usize))
+            }
+            TYPE_GENERICINST => self.generic_instance_name(self.pointer(address), depth),
+            TYPE_VAR | TYPE_MVAR => self
+                .metadata
+                .generic_parameters
+                .get(data as usize)
+                .map_or("?", |&name| self.metadata.string(name))
+                .to_string(),
+            kind => builtin_type_name(kind).unwrap_or("?").to_string(),
+        };
+        self.names.borrow_mut().insert(address, name.clone());
+        name
+    }
+
+    // Il2CppGenericClass: the generic type (an index before 27, a pointer
+    // to its Il2CppType after), then the class Il2CppGenericInst with the
+    // argument count and argument type pointers.
+    fn generic_instance_name(&self, generic_class: Option<u64>, depth: usize) -> String {
+        let Some(generic_class) = generic_class else {
+            return "?".to_string();
+        };
+        let definition = if self.metadata.revision >= Revision::V27 {
+            self.pointer(generic_class)
+                .and_then(|address| self.type_parts(address))
+                .map(|(data, _, _)| data)
+        } else {
+            self.image.read_value(generic_class, self.image.pointer_size)
+        };
+        let base = match definition {
+            Some(index) => self.definition_name(index, false, depth),
+            None => "?".to_string(),
+        };
+
+        let instance = self.pointer_at(generic_class, 1);
+        let count = instance
+            .and_then(|instance| self.image.read_value(instance, 4))
+            .unwrap_or(0);
+        let arguments = instance.and_then(|instance| self.pointer_at(instance, 1));
+        let arguments: Vec<String> = (0..count.min(32))
+            .map(|i| {
+                match arguments.and_then(|arguments| self.pointer_at(arguments, i)) {
+                    Some(argument) => self.type_name(argument, depth + 1),
+                    None => "?".to_string(),
+                }
+            })
+            .collect();
+        format!("{}<{}>", base, arguments.join(", "))
+    }
+
+    // "Outer.Inner", with "<T, U>" when `with_parameters` is set; the
+    // metadata's "List`1" arity suffix is dropped either way.
+    fn definition_name(&self, index: u64, with_parameters: bool, depth: usize) -> String {
+        let Some(definition) = self.metadata.types.get(index as usize) else {
+            return "?".to_string();
+        };
+        let raw = self.metadata.string(definition.name_index);
+        let mut name = raw.split('`').next().unwrap_or(raw).to_string();
+
+        if with_parameters {
+            if let Some(container) = usize::try_from(definition.generic_container)
+                .ok()
+                .and_then(|index| self.metadata.generic_containers.get(index))
+            {
+                let parameters: Vec<&str> = (0..container.type_argc)
+                    .filter_map(|i| {
+                        let name = self
+                            .metadata
+                            .generic_parameters
+                            .get(container.parameter_start as usize + i as usize)?;
+                        Some(self.metadata.string(*name))
+                    })
+                    .collect();
+                name = format!("{}<{}>", name, parameters.join(", "));
+            }
+        }
+
+        if definition.declaring_type >= 0 && depth < MAX_TYPE_DEPTH {
+            let declaring = self
+                .type_address(definition.declaring_type)
+                .and_then(|address| self.type_parts(address));
+            if let Some((declaring, _, _)) = declaring {
+                name = format!("{}.{}", self.definition_name(declaring, false, depth + 1), name);
+            }
+        }
+        name
+    }
+
+    fn class(&self, index: usize, definition: &TypeDefinition) -> ClassDef {
+        let kind = if definition.is_enum() {
+            TypeKind::Enum
+        } else if definition.is_value_type() {
+            TypeKind::Struct
+        } else if definition.flags & TYPE_ATTRIBUTE_INTERFACE != 0 {
+            TypeKind::Interface
+        } else {
+            TypeKind::Class
+        };
+
+        let mut modifiers = vec![match definition.flags & 0x7 {
+            1 | 2 => "public",
+            3 => "private",
+            4 => "protected",
+            _ => "internal",
+        }
+        .to_string()];
+        let (is_abstract, is_sealed) = (
+            definition.flags & TYPE_ATTRIBUTE_ABSTRACT != 0,
+            definition.flags & TYPE_ATTRIBUTE_SEALED != 0,
+        );
+        if is_abstract && is_sealed {
+            modifiers.push("static".to_string());
+        } else if is_abstract && kind != TypeKind::Interface {
+            modifiers.push("abstract".to_string());
+        } else if is_sealed && kind == TypeKind::Class {
+            modifiers.push("sealed".to_string());
+        }
+
+        let parent = Some(definition.parent)
+            .filter(|&parent| kind == TypeKind::Class && parent >= 0)
+            .map(|parent| self.type_name_at(parent))
+            .filter(|parent| parent != "object");
+        let interfaces = (0..definition.interfaces_count as usize)
+            .filter_map(|i| self.metadata.interfaces.get(definition.interfaces_start as usize + i))
+            .map(|&interface| self.type_name_at(interface))
+            .collect();
+
+        ClassDef {
+            namespace: self.metadata.string(definition.namespace_index).to_string(),
+            name: self.definition_name(index as u64, true, 0),
+            kind,
+            modifiers,
+            parent,
+            interfaces,
+            type_def_index: Some(index as u32),
+            location: Location::TypeDef(index as u32),
+            fields: Vec::new(),
+            properties: Vec::new(),
+            methods: Vec::new(),
+        }
+    }
+
+    fn field(&self, type_index: usize, definition: &TypeDefinition, i: usize) -> FieldDef {
+        let field_index = definition.field_start as usize + i;
+        let field = &self.metadata.fields[field_index];
+        let attributes = self.type_attributes(field.type_index);
+        let type_name = self.type_name_at(field.type_index);
+        let name = self.metadata.string(field.name_index).to_string();
+
+        let mut modifiers = vec![match attributes & 0x7 {
+            1 => "private",
+            4 => "protected",
+            5 => "protected internal",
+            6 => "public",
+            _ => "internal",
+        }
+        .to_string()];
+        let is_static = attributes & FIELD_ATTRIBUTE_STATIC != 0;
+        if attributes & FIELD_ATTRIBUTE_LITERAL != 0 {
+            modifiers.push("const".to_string());
+        } else {
+            if is_static {
+                modifiers.push("static".to_string());
+            }
+            if attributes & FIELD_ATTRIBUTE_INIT_ONLY != 0 {
+                modifiers.push("readonly".to_string());
+            }
+        }
+
+        // Constants have no storage; thread statics report -1. Value type
+        // offsets include the object header, which dump.cs leaves out.
+        let offset = Some(attributes & FIELD_ATTRIBUTE_LITERAL == 0)
+            .filter(|&stored| stored)
+            .and_then(|_| self.pointer_at(self.field_offsets, type_index as u64))
+            .and_then(|offsets| self.image.read_value(offsets.checked_add(4 * i as u64)?, 4))
+            .map(|offset| offset as u32 as i32)
+            .filter(|&offset| offset >= 0)
+            .map(|offset| {
+                let header = if definition.is_value_type() && !is_static {
+                    2 * self.pointer_size as i64
+                } else {
+                    0
+                };
+                (offset as i64 - header).max(0) as u64
+            });
+
+        let mut text = format!("\t{} {} {};", modifiers.join(" "), type_name, name);
+        if let Some(offset) = offset {
+            text.push_str(&format!(" // 0x{:X}", offset));
+        }
+        FieldDef {
+            modifiers,
+            type_name,
+            name,
+            offset,
+            location: Location::Field(field_index as u32),
+            text,
+        }
+    }
+
+    fn method(&self, index: usize, address: Option<u64>) -> MethodDef {
+        let method = &self.metadata.methods[index];
+        let flags = method.flags;
+
+        let mut modifiers = vec![match flags & 0x7 {
+            1 => "private",
+            4 => "protected",
+            5 => "protected internal",
+            6 => "public",
+            _ => "internal",
+        }
+        .to_string()];
+        if flags & METHOD_ATTRIBUTE_STATIC != 0 {
+            modifiers.push("static".to_string());
+        }
+        let new_slot = flags & METHOD_ATTRIBUTE_NEW_SLOT != 0;
+        if flags & METHOD_ATTRIBUTE_ABSTRACT != 0 {
+            modifiers.push("abstract".to_string());
+            if !new_slot {
+                modifiers.push("override".to_string());
+            }
+        } else if flags & METHOD_ATTRIBUTE_FINAL != 0 {
+            if !new_slot {
+                modifiers.extend(["sealed".to_string(), "override".to_string()]);
+            }
+        } else if flags & METHOD_ATTRIBUTE_VIRTUAL != 0 {
+            modifiers.push(if new_slot { "virtual" } else { "override" }.to_string());
+        }
+        if flags & METHOD_ATTRIBUTE_PINVOKE_IMPL != 0 {
+            modifiers.push("extern".to_string());
+        }
+
+        let params: Vec<ParamDef> = (0..method.parameter_count as usize)
+            .filter_map(|i| self.metadata.parameters.get(method.parameter_start as usize + i))
+            .map(|parameter| ParamDef {
+                type_name: self.type_name_at(parameter.type_index),
+                name: self.metadata.string(parameter.name_index).to_string(),
+            })
+            .collect();
+        let return_type = self.type_name_at(method.return_type);
+        let name = self.metadata.string(method.name_index).to_string();
+        let text = format!(
+            "\t{} {} {}({}) {{ }}",
+            modifiers.join(" "),
+            return_type,
+            name,
+            params
+                .iter()
+                .map(|param| format!("{} {}", param.type_name, param.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        MethodDef {
+            modifiers,
+            return_type,
+            name,
+            params,
+            rva: address,
+            offset: address.and_then(|address| self.image.file_offset(address)),
+            va: address.and_then(|address| address.checked_add(self.image.image_base)),
+            slot: (method.slot != 0xFFFF).then_some(method.slot as u32),
+            location: Location::Method(index as u32),
+            text,
+        }
+    }
+
+    // Method index to code address. From 24.2 each image has its own
+    // Il2CppCodeGenModule indexed by token; before that one global table in
+    // Il2CppCodeRegistration is indexed by the method's method_index.
+    fn method_addresses(&self) -> HashMap<usize, u64> {
+        let mut addresses = HashMap::new();
+
+        if self.metadata.revision <= Revision::V24_1 {
+            let count = self.metadata.methods.iter().map(|m| i64::from(m.method_index) + 1).max().unwrap_or(0);
+            if let Some(table) = find_method_pointer_table(self.image, count.max(0) as u64) {
+                for (index, method) in self.metadata.methods.iter().enumerate() {
+                    let Ok(slot) = u64::try_from(method.method_index) else { continue };
+                    if let Some(address) = self.pointer_at(table, slot) {
+                        addresses.insert(index, address);
+                    }
+                }
+            }
+            return addresses;
+        }
+
+        for (image, module) in self.metadata.images.iter().zip(self.code_gen_modules()) {
+            let Some(table) = module else { continue };
+            for definition in &self.metadata.types[image.types()] {
+                for index in definition.method_start as usize
+                    ..definition.method_start as usize + definition.method_count as usize
+                {
+                    let rid = (self.metadata.methods[index].token & 0x00FF_FFFF) as u64;
+                    if rid == 0 {
+                        continue;
+                    }
+                    if let Some(address) = self.pointer_at(table, rid - 1) {
+                        addresses.insert(index, address);
+                    }
+                }
+            }
+        }
+        addresses
+    }
+
+    // Each image's method pointer table, found through its
+    // Il2CppCodeGenModule: a pointer to the image name string, the method
+    // count, then the table pointer.
+    fn code_gen_modules(&self) -> Vec<Option<u64>> {
+        let mut name_addresses: HashMap<u64, usize> = HashMap::new();
+        for (i, image) in self.metadata.images.iter().enumerate() {
+            let mut needle = self.metadata.string(image.name_index).as_bytes().to_vec();
+            needle.push(0);
+            for segment in &self.image.segments {
+                for start in memmem::find_iter(segment.data, &needle) {
+                    name_addresses.insert(segment.address + start as u64, i);
+                }
+            }
+        }
+
+        let targets: HashSet<u64> = name_addresses.keys().copied().collect();
+        let mut modules = vec![None; self.metadata.images.len()];
+        for (slot, pointer) in pointers_to(self.image, &targets) {
+            let i = name_addresses[&pointer];
+            if modules[i].is_some() {
+                continue;
+            }
+            let image = &self.metadata.images[i];
+            let method_count: u64 = self.metadata.types[image.types()].iter().map(|t| t.method_count as u64).sum();
+            let count = self.image.read_value(slot + self.pointer_size, 4);
+            if count == Some(method_count) {
+                modules[i] = self.pointer(slot + 2 * self.pointer_size);
+            }
+        }
+        modules
+    }
+}
+
+fn builtin_type_name(kind: u8) -> Option<&'static str> {
+    Some(match kind {
+        0x01 => "void",
+        0x02 => "bool",
+        0x03 => "char",
+        0x04 => "sbyte",
+        0x05 => "byte",
+        0x06 => "short",
+        0x07 => "ushort",
+        0x08 => "int",
+        0x09 => "uint",
+        0x0A => "long",
+        0x0B => "ulong",
+        0x0C => "float",
+        0x0D => "double",
+        0x0E => "string",
+        0x16 => "TypedReference",
+        0x18 => "IntPtr",
+        0x19 => "UIntPtr",
+        0x1C => "object",
+        _ => return None,
+    })
+}
+
+// Calls `visit` with the address and value of every pointer-aligned word.
+fn for_each_word(image: &BinaryImage, mut visit: impl FnMut(u64, u64)) {
+    let size = image.pointer_size;
+    for segment in &image.segments {
+        let skip = (size - (segment.address as usize % size)) % size;
+        let Some(data) = segment.data.get(skip..) else { continue };
+        for (i, word) in data.chunks_exact(size).enumerate() {
+            let value = word.iter().rev().fold(0, |value, &byte| (value << 8) | byte as u64);
+            visit(segment.address + (skip + i * size) as u64, value);
+        }
+    }
+}
+
+// (slot, target) for every stored pointer whose target is in `targets`.
+fn pointers_to(image: &BinaryImage, targets: &HashSet<u64>) -> Vec<(u64, u64)> {
+    let mut found = Vec::new();
+    for_each_word(image, |address, _| {
+        if let Ok(pointer) = image.read_pointer(address) {
+            if targets.contains(&pointer) {
+                found.push((address, pointer));
+            }
+        }
+    });
+    found
+}
+
+// Il2CppMetadataRegistration is eight (count, pointer) pairs. The field
+// offset and type definition size counts (pairs 5 and 6) both equal the
+// number of type definitions, which is distinctive enough to find it.
+fn find_metadata_registration(metadata: &Metadata, image: &BinaryImage) -> Option<u64> {
+    let count = metadata.types.len() as u64;
+    let size = image.pointer_size as u64;
+    let mut candidates = Vec::new();
+    for_each_word(image, |address, value| {
+        if value == count && address >= 10 * size {
+            candidates.push(address);
+        }
+    });
+
+    candidates.into_iter().find_map(|address| {
+        let word = |index: u64| image.read_value(address + index * size, image.pointer_size);
+        if word(2)? != count {
+            return None;
+        }
+        let registration = address - 10 * size;
+        let types_count = image.read_value(registration + 6 * size, image.pointer_size)?;
+        let types = image.read_pointer(registration + 7 * size).ok()?;
+        let field_offsets = image.read_pointer(address + size).ok()?;
+        let valid = (1..10_000_000).contains(&types_count)
+            && image.read(types, image.pointer_size).is_some()
+            && image.read(field_offsets, image.pointer_size).is_some();
+        valid.then_some(registration)
+    })
+}
+
+// Before 24.2, Il2CppCodeRegistration opens with the method pointer count
+// and table; accept the first such pair whose entries point at code.
+fn find_method_pointer_table(image: &BinaryImage, count: u64) -> Option<u64> {
+    if count == 0 {
+        return None;
+    }
+    let size = image.pointer_size as u64;
+    let is_code = |address: u64| {
+        image
+            .segments
+            .iter()
+            .any(|s| s.executable && address >= s.address && address - s.address < s.data.len() as u64)
+    };
+    let mut candidates = Vec::new();
+    for_each_word(image, |address, value| {
+        if value == count {
+            candidates.push(address);
+        }
+    });
+
+    candidates.into_iter().find_map(|address| {
+        let table = image.read_pointer(address + size).ok()?;
+        let checked = (0..count.min(8))
+            .filter_map(|i| image.read_pointer(table.checked_add(i * size)?).ok())
+            .filter(|&pointer| pointer != 0)
+            .collect::<Vec<_>>();
+        (!checked.is_empty() && checked.iter().all(|&pointer| is_code(pointer))).then_some(table)
+    })
+}
@@ -0,0 +1,345 @@
+use crate::metadata::METADATA_SANITY;
+use memchr::memmem;
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
+use std::ops::Deref;
+
+// Layout written by `dump`: the magic, then one record per region (base: u64,
+// size: u64, flags: u32, all little endian) followed by `size` bytes of
+// memory. The flags are the host's page protections and are not read back.
+pub const MEMORY_DUMP_MAGIC: &[u8; 8] = b"OFDUMP\x00\x01";
+pub const REGION_READ: u32 = 0x1;
+pub const REGION_WRITE: u32 = 0x2;
+pub const REGION_EXECUTE: u32 = 0x4;
+
+const SNIFF_LEN: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    Text,
+    Utf16Text,
+    Elf,
+    Pe,
+    MemoryDump,
+    Metadata,
+    ScriptJson,
+    Header,
+    Raw,
+    Gzip,
+    Zip,
+}
+
+impl InputKind {
+    pub fn name(&self) -> &str {
+        match self {
+            InputKind::Text => "UTF-8 text",
+            InputKind::Utf16Text => "UTF-16 text",
+            InputKind::Elf => "ELF binary",
+            InputKind::Pe => "PE binary",
+            InputKind::MemoryDump => "memory dump",
+            InputKind::Metadata => "IL2CPP metadata",
+            InputKind::ScriptJson => "Il2CppDumper script.json",
+            InputKind::Header => "Il2CppDumper il2cpp.h",
+            InputKind::Raw => "raw binary",
+            InputKind::Gzip => "gzip archive",
+            InputKind::Zip => "zip archive",
+        }
+    }
+
+    // Names accepted by `--input-kind`. Archives are only ever detected.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "text" => Ok(InputKind::Text),
+            "utf16" => Ok(InputKind::Utf16Text),
+            "elf" => Ok(InputKind::Elf),
+            "pe" => Ok(InputKind::Pe),
+            "memdump" => Ok(InputKind::MemoryDump),
+            "metadata" => Ok(InputKind::Metadata),
+            "script" => Ok(InputKind::ScriptJson),
+            "header" => Ok(InputKind::Header),
+            "raw" => Ok(InputKind::Raw),
+            _ => Err(format!(
+                "unknown input kind '{}' (expected text, utf16, elf, pe, memdump, metadata, script, header or raw)",
+                name
+            )),
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        matches!(self, InputKind::Text | InputKind::Utf16Text)
+    }
+
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(MEMORY_DUMP_MAGIC) {
+            InputKind::MemoryDump
+        } else if bytes.starts_with(&METADATA_SANITY.to_le_bytes()) {
+            InputKind::Metadata
+        } else if bytes.starts_with(b"\x7fELF") {
+            InputKind::Elf
+        } else if bytes.starts_with(b"MZ") {
+            InputKind::Pe
+        } else if bytes.starts_with(&[0x1F, 0x8B]) {
+            InputKind::Gzip
+        } else if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+            InputKind::Zip
+        } else if looks_like_utf16(bytes) {
+            InputKind::Utf16Text
+        } else if looks_like_utf8(bytes) {
+            detect_text_kind(bytes)
+        } else {
+            InputKind::Raw
+        }
+    }
+}
+
+// Il2CppDumper writes script.json and il2cpp.h next to dump.cs; both say
+// what they are within the first few lines.
+fn detect_text_kind(bytes: &[u8]) -> InputKind {
+    let head = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let first = head
+        .strip_prefix(b"\xEF\xBB\xBF")
+        .unwrap_or(head)
+        .iter()
+        .find(|b| !b.is_ascii_whitespace());
+    if first == Some(&b'{') && memmem::find(head, b"\"ScriptMethod\"").is_some() {
+        InputKind::ScriptJson
+    } else if memmem::find(head, b"Il2CppMethodPointer").is_some() {
+        InputKind::Header
+    } else {
+        InputKind::Text
+    }
+}
+
+fn looks_like_utf8(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        // The sniff window may cut a multi-byte character in half.
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+// Without a BOM, ASCII text in UTF-16 leaves every other byte zero.
+fn looks_like_utf16(bytes: &[u8]) -> bool {
+    if bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF]) {
+        return true;
+    }
+    let head = &bytes[..bytes.len().min(SNIFF_LEN) & !1];
+    if head.len() < 4 {
+        return false;
+    }
+    let zero_even = head.iter().step_by(2).filter(|&&b| b == 0).count();
+    let zero_odd = head.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    let half = head.len() / 2;
+    (zero_odd == half && zero_even == 0) || (zero_even == half && zero_odd == 0)
+}
+
+pub struct Input {
+    pub kind: InputKind,
+    pub bytes: InputBytes,
+}
+
+// The file contents. Files are memory-mapped so multi-gigabyte dumps are
+// paged in on demand instead of copied into memory; anything that cannot be
+// mapped (pipes, empty files) is read normally.
+pub enum InputBytes {
+    Mapped(Mmap),
+    Read(Vec<u8>),
+}
+
+impl Deref for InputBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            InputBytes::Mapped(map) => map,
+            InputBytes::Read(bytes) => bytes,
+        }
+    }
+}
+
+impl Input {
+    pub fn read(path: &str, forced: Option<InputKind>) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Error reading file: {}", e))?;
+        let is_file = file.metadata().is_ok_and(|m| m.is_file() && m.len() > 0);
+        // SAFETY: the map is read-only; as with any mapped file, the input
+        // must not be truncated while it is being scanned.
+        let bytes = match is_file.then(|| unsafe { Mmap::map(&file) }) {
+            Some(Ok(map)) => InputBytes::Mapped(map),
+            _ => InputBytes::Read(fs::read(path).map_err(|e| format!("Error reading file: {}", e))?),
+        };
+        let kind = forced.unwrap_or_else(|| InputKind::detect(&bytes));
+        Ok(Input { kind, bytes })
+    }
+
+    pub fn lines(&self) -> Result<TextLines<&[u8]>, String> {
+        TextLines::new(&self.bytes[..], self.kind)
+    }
+}
+
+// Decodes text input one line at a time, so only the current line is ever
+// held as a String. Line endings ("\n" or "\r\n") are stripped.
+pub struct TextLines<R> {
+    reader: R,
+    // None for UTF-8, otherwise whether UTF-16 is big endian.
+    utf16: Option<bool>,
+    position: usize,
+    raw: Vec<u8>,
+    line: String,
+}
+
+impl<R: BufRead> TextLines<R> {
+    pub fn new(mut reader: R, kind: InputKind) -> Result<Self, String> {
+        let head = reader.fill_buf().map_err(|e| format!("Error reading file: {}", e))?;
+        let (utf16, bom) = match kind {
+            InputKind::Text | InputKind::ScriptJson | InputKind::Header
+                if head.starts_with(b"\xEF\xBB\xBF") =>
+            {
+                (None, 3)
+            }
+            InputKind::Text | InputKind::ScriptJson | InputKind::Header => (None, 0),
+            InputKind::Utf16Text if head.starts_with(&[0xFF, 0xFE]) => (Some(false), 2),
+            InputKind::Utf16Text if head.starts_with(&[0xFE, 0xFF]) => (Some(true), 2),
+            InputKind::Utf16Text => (Some(head.first() == Some(&0)), 0),
+            other => return Err(format!("input was detected as {}, not text", other.name())),
+        };
+        reader.consume(bom);
+
+        Ok(TextLines {
+            reader,
+            utf16,
+            position: bom,
+            raw: Vec::new(),
+            line: String::new(),
+        })
+    }
+
+    pub fn next_line(&mut self) -> Result<Option<&str>, String> {
+        match self.utf16 {
+            None => self.next_utf8_line(),
+            Some(big_endian) => self.next_utf16_line(big_endian),
+        }
+    }
+
+    fn next_utf8_line(&mut self) -> Result<Option<&str>, String> {
+        self.raw.clear();
+        let read = self
+            .reader
+            .read_until(b'\n', &mut self.raw)
+            .map_err(|e| format!("Error reading file: {}", e))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        let start = self.position;
+        self.position += read;
+
+        let mut line = &self.raw[..];
+        if let Some(rest) = line.strip_suffix(b"\n") {
+            line = rest.strip_suffix(b"\r").unwrap_or(rest);
+        }
+        std::str::from_utf8(line).map(Some).map_err(|e| {
+            format!(
+                "input was read as UTF-8 text but byte 0x{:X} is not valid UTF-8 \
+                 (use --input-kind to override)",
+                start + e.valid_up_to()
+            )
+        })
+    }
+
+    fn next_utf16_line(&mut self, big_endian: bool) -> Result<Option<&str>, String> {
+        let invalid = || "input was read as UTF-16 text but contains invalid surrogates".to_string();
+        self.line.clear();
+
+        let Some(mut unit) = self.next_unit(big_endian)? else {
+            return Ok(None);
+        };
+        loop {
+            let c = if (0xD800..0xDC00).contains(&unit) {
+                let low = self
+                    .next_unit(big_endian)?
+                    .filter(|low| (0xDC00..0xE000).contains(low))
+                    .ok_or_else(invalid)?;
+                char::from_u32(0x10000 + ((unit as u32 - 0xD800) << 10) + (low as u32 - 0xDC00))
+            } else {
+                char::from_u32(unit as u32)
+            }
+            .ok_or_else(invalid)?;
+
+            if c == '\n' {
+                if self.line.ends_with('\r') {
+                    self.line.pop();
+                }
+                break;
+            }
+            self.line.push(c);
+            match self.next_unit(big_endian)? {
+                Some(next) => unit = next,
+                None => break,
+            }
+        }
+        Ok(Some(&self.line))
+    }
+
+    // A trailing odd byte is ignored.
+    fn next_unit(&mut self, big_endian: bool) -> Result<Option<u16>, String> {
+        let mut pair = [0u8; 2];
+        match self.reader.read_exact(&mut pair) {
+            Ok(()) => {
+                self.position += 2;
+                Ok(Some(if big_endian {
+                    u16::from_be_bytes(pair)
+                } else {
+                    u16::from_le_bytes(pair)
+                }))
+            }
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(format!("Error reading file: {}", e)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DumpRegion {
+    pub base: u64,
+    pub file_offset: usize,
+    pub size: usize,
+}
+
+pub fn write_region_header(out: &mut impl Write, base: u64, size: u64, flags: u32) -> io::Result<()> {
+    out.write_all(&base.to_le_bytes())?;
+    out.write_all(&size.to_le_bytes())?;
+    out.write_all(&flags.to_le_bytes())
+}
+
+pub fn parse_memory_dump(bytes: &[u8]) -> Result<Vec<DumpRegion>, String> {
+    let mut regions = Vec::new();
+    let mut position = MEMORY_DUMP_MAGIC.len();
+
+    while position < bytes.len() {
+        let header = bytes
+            .get(position..position + 20)
+            .ok_or_else(|| format!("memory dump truncated at byte 0x{:X}", position))?;
+        let base = u64::from_le_bytes(header[0..8].try_into().unwrap());
+        let size = u64::from_le_bytes(header[8..16].try_into().unwrap()) as usize;
+        let file_offset = position + 20;
+
+        if bytes.len() - file_offset < size {
+            return Err(format!(
+                "memory dump region at 0x{:X} claims {} bytes but the file ends first",
+                base, size
+            ));
+        }
+        regions.push(DumpRegion {
+            base,
+            file_offset,
+            size,
+        });
+        position = file_offset + size;
+    }
+
+    Ok(regions)
+}
@@ -0,0 +1,268 @@
+use crate::expr::Expr;
+use crate::exporter::{cpp_const_name, rust_const_name};
+use crate::models::{
+    FieldAnchor, OffsetCategory, OffsetValue, Operand, OperandEncoding, Target, TargetSet,
+};
+use crate::signature::Signature;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintIssue {
+    pub severity: Severity,
+    pub target: Option<String>,
+    pub message: String,
+}
+
+impl LintIssue {
+    fn error(target: Option<&str>, message: String) -> Self {
+        LintIssue {
+            severity: Severity::Error,
+            target: target.map(str::to_string),
+            message,
+        }
+    }
+
+    fn warning(target: Option<&str>, message: String) -> Self {
+        LintIssue {
+            severity: Severity::Warning,
+            target: target.map(str::to_string),
+            message,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.target {
+            Some(target) => write!(f, "{}: {}", target, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+// Inheritance merges same-named targets, so a file that declares a name twice
+// has to be caught before the set is resolved.
+pub fn lint_target_set(set: &TargetSet) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut seen_names: HashSet<&str> = HashSet::new();
+
+    for group in &set.categories {
+        for def in &group.targets {
+            if let Some(name) = def.target.name() {
+                if !seen_names.insert(name) {
+                    issues.push(LintIssue::error(Some(name), "duplicate target name".to_string()));
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+// Checks a resolved target set for everything that would otherwise only show
+// up as a silent NOT FOUND or a broken export.
+pub fn lint_config(config: &[(OffsetCategory, Vec<Target>)]) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+
+    let names: HashSet<&str> = config
+        .iter()
+        .flat_map(|(_, targets)| targets)
+        .filter_map(|t| t.name())
+        .collect();
+    let mut seen_names: HashSet<&str> = HashSet::new();
+    let mut selectors: HashMap<String, &str> = HashMap::new();
+    let mut cpp_names: HashMap<String, &str> = HashMap::new();
+    let mut rust_names: HashMap<String, &str> = HashMap::new();
+
+    for (category, targets) in config {
+        if targets.iter().all(|t| t.name().is_none()) {
+            issues.push(LintIssue::warning(
+                None,
+                format!("category '{}' has no targets", category.name()),
+            ));
+        }
+
+        for target in targets {
+            let Some(name) = target.name() else { continue };
+
+            if !seen_names.insert(name) {
+                issues.push(LintIssue::error(Some(name), "duplicate target name".to_string()));
+            }
+
+            for (language, const_name, taken) in [
+                ("C++", cpp_const_name(name), &mut cpp_names),
+                ("Rust", rust_const_name(name), &mut rust_names),
+            ] {
+                if !is_identifier(&const_name) {
+                    issues.push(LintIssue::error(
+                        Some(name),
+                        format!("exports as '{}', which is not a valid {} identifier", const_name, language),
+                    ));
+                } else if let Some(other) = taken.insert(const_name.clone(), name) {
+                    if other != name {
+                        issues.push(LintIssue::error(
+                            Some(name),
+                            format!("exports as '{}' in {}, same as '{}'", const_name, language, other),
+                        ));
+                    }
+                }
+            }
+
+            // Two lookups with the same selector always find the same offset.
+            if let Some(other) = selector_key(target).and_then(|key| selectors.insert(key, name)) {
+                issues.push(LintIssue::error(
+                    Some(name),
+                    format!("looks up the same thing as '{}'; use expr = \"${{{}}}\" for an alias", other, other),
+                ));
+            }
+
+            for message in lint_target(target, &names) {
+                issues.push(LintIssue::error(Some(name), message));
+            }
+        }
+    }
+
+    issues
+}
+
+// The target without its name, for lookups; fixed values and expressions may
+// repeat.
+fn selector_key(target: &Target) -> Option<String> {
+    if matches!(target, Target::Fixed { .. } | Target::Expr { .. } | Target::Separator) {
+        return None;
+    }
+    let mut value = serde_json::to_value(target).ok()?;
+    value.as_object_mut()?.remove("name");
+    Some(value.to_string())
+}
+
+fn lint_target(target: &Target, names: &HashSet<&str>) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    match target {
+        Target::Fixed { hex, .. } => {
+            if let Err(e) = OffsetValue::parse(hex) {
+                messages.push(e);
+            }
+        }
+        Target::Pattern { pattern, .. } => {
+            if pattern.is_empty() {
+                messages.push("pattern is empty".to_string());
+            }
+        }
+        Target::Regex { pattern, .. } => check_regex("pattern", pattern, &mut messages),
+        Target::AoB { signature, operand, expect, .. } => match Signature::parse(signature) {
+            Ok(signature) => {
+                if *expect == 0 {
+                    messages.push("expect must be at least 1".to_string());
+                }
+                if let Some(operand) = operand {
+                    check_operand(operand, signature.value.len(), &mut messages);
+                }
+            }
+            Err(e) => messages.push(e),
+        },
+        Target::Field { class, field, .. } | Target::StaticField { class, field, .. } => {
+            check_regex("class", class, &mut messages);
+            if let Some(type_name) = &field.type_name {
+                check_regex("field type", type_name, &mut messages);
+            }
+            if let Some(field_name) = &field.name {
+                check_regex("field name", field_name, &mut messages);
+            }
+            if let Some(FieldAnchor::After(anchor) | FieldAnchor::Before(anchor)) = &field.anchor {
+                check_regex("anchor", anchor, &mut messages);
+            }
+        }
+        Target::Method { class, method, .. } => {
+            check_regex("class", class, &mut messages);
+            if let Some(method_name) = &method.name {
+                check_regex("method name", method_name, &mut messages);
+            }
+            if let Some(return_type) = &method.return_type {
+                check_regex("return type", return_type, &mut messages);
+            }
+            for param in method.params.iter().flatten() {
+                check_regex("parameter type", param, &mut messages);
+            }
+        }
+        Target::TypeIndex { class, .. } => check_regex("class", class, &mut messages),
+        Target::Expr { expr, .. } => match Expr::parse(expr) {
+            Ok(parsed) => {
+                for reference in parsed.references() {
+                    if !names.contains(reference) {
+                        messages.push(format!("unknown target '{}'", reference));
+                    }
+                }
+            }
+            Err(e) => messages.push(e),
+        },
+        Target::Separator => {}
+    }
+
+    messages
+}
+
+fn check_operand(operand: &Operand, signature_len: usize, messages: &mut Vec<String>) {
+    if operand.encoding != OperandEncoding::Value {
+        if operand.offset + 4 > signature_len {
+            messages.push(format!(
+                "operand instruction at byte {} is past the {}-byte signature",
+                operand.offset, signature_len
+            ));
+        }
+        return;
+    }
+    if !matches!(operand.size, 1 | 2 | 4 | 8) {
+        messages.push(format!("operand size {} is not 1, 2, 4 or 8", operand.size));
+    }
+    let end = operand.offset + operand.size;
+    if end > signature_len {
+        messages.push(format!(
+            "operand ends at byte {} but the signature is only {} bytes",
+            end, signature_len
+        ));
+    }
+    if let Some(length) = operand.instruction_length {
+        if end > length {
+            messages.push(format!(
+                "operand ends at byte {}, past the {}-byte instruction",
+                end, length
+            ));
+        }
+    }
+}
+
+fn check_regex(what: &str, pattern: &str, messages: &mut Vec<String>) {
+    if let Err(e) = Regex::new(pattern) {
+        messages.push(format!("invalid {} regex '{}': {}", what, pattern, regex_error(&e)));
+    }
+}
+
+// The last line of a regex error, which names the problem without the
+// pattern excerpt above it.
+pub fn regex_error(error: &regex::Error) -> String {
+    let detail = error.to_string();
+    let summary = detail.lines().last().unwrap_or(&detail).trim();
+    summary.strip_prefix("error: ").unwrap_or(summary).to_string()
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name != "_"
+}
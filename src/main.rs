@@ -1,21 +1,45 @@
+mod arm64;
 mod config;
 mod dumper;
+mod elf;
 mod exporter;
+mod expr;
+mod header;
+mod il2cpp;
+mod input;
+mod lint;
+mod metadata;
 mod models;
+mod parser;
+mod pe;
+mod remap;
 mod scanner;
+mod script;
+mod search;
+mod signature;
 mod ui;
 
 use clap::{Parser, Subcommand};
-use config::{detect_game_variant, get_game_config};
+use config::{
+    builtin_target_set_names, detect_game_variant_in, get_game_config, load_named_target_set,
+    resolve_target_set, TargetConfig,
+};
+use input::{Input, InputKind};
+use lint::{lint_config, lint_target_set};
 use dumper::dump_bluestacks_memory;
 use exporter::export_results;
 use models::{ExportFormat, GameVariant};
+use parser::parse_dump;
+use remap::{apply_renames, remap};
 use rfd::FileDialog;
-use scanner::scan_file;
-use std::path::PathBuf;
+use scanner::{batch_scan, scan_file, scan_input, scan_metadata};
+use std::fs;
+use std::path::{Path, PathBuf};
 use ui::{
     get_export_selection, get_game_selection, print_error, print_export_menu, print_game_menu,
-    print_header, print_info, print_results, print_statistics, print_success, wait_for_enter,
+    print_batch_summary, print_header, print_info, print_lint_report, print_renames, print_results, print_statistics, print_success,
+    print_target_changes, print_timings,
+    wait_for_enter,
 };
 
 #[derive(Parser)]
@@ -41,11 +65,81 @@ enum Commands {
         
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Target file (TOML/JSON) or profile name to use instead of the built-in set
+        #[arg(short, long)]
+        targets: Option<String>,
+
+        /// Show the matched dump text under each offset
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Treat the file as text, utf16, elf, pe, memdump, metadata, script, header or raw
+        /// instead of detecting it
+        #[arg(long)]
+        input_kind: Option<String>,
+
+        /// libil2cpp.so or GameAssembly.dll to read with a global-metadata.dat
+        /// (default: look next to it and in an extracted APK's lib/ folders)
+        #[arg(long)]
+        binary: Option<PathBuf>,
+
+        /// Print how long each target took to find, slowest first
+        #[arg(long)]
+        timings: bool,
     },
     Dump {
         #[arg(short, long, default_value = "memory_dump.bin")]
         output: String,
     },
+    /// Scan many dumps in parallel and summarize found/missing per file
+    Batch {
+        /// Files, directories or glob patterns (e.g. "dumps/*.cs")
+        #[arg(required = true)]
+        inputs: Vec<String>,
+
+        #[arg(short, long)]
+        game: Option<String>,
+
+        /// Target file (TOML/JSON) or profile name to use for every file
+        #[arg(short, long)]
+        targets: Option<String>,
+
+        /// Worker threads (default: one per CPU)
+        #[arg(short = 'j', long)]
+        threads: Option<usize>,
+
+        /// Only print the summary table, not each file's offsets
+        #[arg(long)]
+        summary_only: bool,
+    },
+    /// Check target sets for mistakes; exits non-zero if any errors are found
+    Lint {
+        /// Target files or profile names to check (default: all built-in sets)
+        #[arg(short, long)]
+        targets: Vec<String>,
+    },
+    /// Pair obfuscated classes, fields and methods of two dump.cs versions
+    /// and carry a target set's names from the old one to the new one
+    Remap {
+        /// dump.cs the target set was written against
+        old: PathBuf,
+
+        /// dump.cs of the new game version
+        new: PathBuf,
+
+        /// Target file or profile name to rewrite with the renames
+        #[arg(short, long, requires = "output")]
+        targets: Option<String>,
+
+        /// Where to write the rewritten target set (.toml or .json)
+        #[arg(short, long, requires = "targets")]
+        output: Option<PathBuf>,
+
+        /// Renames below this confidence are listed but not applied
+        #[arg(long, default_value_t = 0.7)]
+        min_confidence: f32,
+    },
 }
 
 fn main() {
@@ -58,7 +152,12 @@ fn main() {
             game,
             export,
             output,
-        }) => run_cli_mode(file, game, export, output),
+            targets,
+            verbose,
+            input_kind,
+            binary,
+            timings,
+        }) => run_cli_mode(file, game, export, output, targets, verbose, input_kind, binary, timings),
         Some(Commands::Dump { output }) => {
             print_header();
             print_info(&format!("Attempting to dump BlueStacks memory to {}...", output));
@@ -68,6 +167,26 @@ fn main() {
             }
             wait_for_enter();
         }
+        Some(Commands::Batch {
+            inputs,
+            game,
+            targets,
+            threads,
+            summary_only,
+        }) => run_batch(inputs, game, targets, threads, summary_only),
+        Some(Commands::Lint { targets }) => run_lint(targets),
+        Some(Commands::Remap {
+            old,
+            new,
+            targets,
+            output,
+            min_confidence,
+        }) => {
+            if let Err(e) = run_remap(&old, &new, targets, output, min_confidence) {
+                print_error(&e);
+                std::process::exit(1);
+            }
+        }
     }
 }
 
@@ -97,17 +216,17 @@ fn run_interactive_mode() {
         Some(3) => GameVariant::FreeFireTela,
         Some(4) => {
             print_info("Auto-detecting game variant...");
-            match std::fs::read_to_string(&file_path) {
-                Ok(content) => match detect_game_variant(&content) {
-                    Some(variant) => {
-                        print_success(&format!("Detected: {}", variant.name()));
-                        variant
-                    }
-                    None => {
-                        print_error("Could not detect game variant. Using Free Fire standard.");
-                        GameVariant::FreeFire
-                    }
-                },
+            match Input::read(file_path.to_str().unwrap(), None)
+                .and_then(|input| detect_game_variant_in(&input))
+            {
+                Ok(Some(variant)) => {
+                    print_success(&format!("Detected: {}", variant.name()));
+                    variant
+                }
+                Ok(None) => {
+                    print_error("Could not detect game variant. Using Free Fire standard.");
+                    GameVariant::FreeFire
+                }
                 Err(_) => {
                     print_error("Error reading file. Using Free Fire standard.");
                     GameVariant::FreeFire
@@ -123,6 +242,9 @@ fn run_interactive_mode() {
     print_info(&format!("Scanning for {} offsets...", game_variant.name()));
 
     let config = get_game_config(game_variant);
+    for issue in lint_config(&config).iter().filter(|i| i.is_error()) {
+        print_error(&format!("Invalid target {}", issue));
+    }
     let results = match scan_file(file_path.to_str().unwrap(), &config) {
         Ok(results) => results,
         Err(e) => {
@@ -132,7 +254,7 @@ fn run_interactive_mode() {
         }
     };
 
-    print_results(&results, game_variant);
+    print_results(&results, game_variant, false);
     print_statistics(&results);
 
     print_export_menu();
@@ -155,7 +277,7 @@ fn run_interactive_mode() {
         };
 
         let output_path = FileDialog::new()
-            .set_file_name(&format!("offsets.{}", extension))
+            .set_file_name(format!("offsets.{}", extension))
             .add_filter("Export file", &[extension])
             .save_file();
 
@@ -172,32 +294,64 @@ fn run_interactive_mode() {
     wait_for_enter();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_cli_mode(
     file: PathBuf,
     game: Option<String>,
     export_format: Option<String>,
     output: Option<PathBuf>,
+    targets: Option<String>,
+    verbose: bool,
+    input_kind: Option<String>,
+    binary: Option<PathBuf>,
+    timings: bool,
 ) {
-    let game_variant = match game.as_deref() {
-        Some("freefire") | Some("ff") => GameVariant::FreeFire,
-        Some("max") => GameVariant::FreeFireMax,
-        Some("tela") => GameVariant::FreeFireTela,
-        Some("auto") | None => {
-            match std::fs::read_to_string(&file) {
-                Ok(content) => detect_game_variant(&content).unwrap_or(GameVariant::FreeFire),
-                Err(_) => GameVariant::FreeFire,
-            }
+    let forced_kind = match input_kind.as_deref().map(InputKind::from_name).transpose() {
+        Ok(kind) => kind,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
-        _ => {
-            eprintln!("Invalid game variant. Using Free Fire standard.");
-            GameVariant::FreeFire
+    };
+    let input = match Input::read(file.to_str().unwrap(), forced_kind) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
     };
+    match forced_kind {
+        Some(kind) => println!("Reading input as {}", kind.name()),
+        None => println!("Detected input: {}", input.kind.name()),
+    }
+
+    let game_variant = parse_game(game.as_deref()).unwrap_or_else(|| {
+        detect_game_variant_in(&input)
+            .ok()
+            .flatten()
+            .unwrap_or(GameVariant::FreeFire)
+    });
 
     println!("Scanning {} for {} offsets...", file.display(), game_variant.name());
 
-    let config = get_game_config(game_variant);
-    let results = match scan_file(file.to_str().unwrap(), &config) {
+    let config = match targets {
+        Some(name) => load_targets_or_exit(&name),
+        None => get_game_config(game_variant),
+    };
+    for issue in lint_config(&config).iter().filter(|i| i.is_error()) {
+        eprintln!("Invalid target {}", issue);
+    }
+    let scanned = match (&binary, input.kind) {
+        (Some(binary), InputKind::Metadata) => {
+            scan_metadata(file.to_str().unwrap(), &input, binary.to_str(), &config)
+        }
+        (Some(_), kind) => Err(format!(
+            "--binary only applies to global-metadata.dat inputs, not {}",
+            kind.name()
+        )),
+        (None, _) => scan_input(file.to_str().unwrap(), &input, &config),
+    };
+    let results = match scanned {
         Ok(results) => results,
         Err(e) => {
             eprintln!("Error: {}", e);
@@ -205,8 +359,11 @@ fn run_cli_mode(
         }
     };
 
-    print_results(&results, game_variant);
+    print_results(&results, game_variant, verbose);
     print_statistics(&results);
+    if timings {
+        print_timings(&results);
+    }
 
     if let Some(format_str) = export_format {
         let format = match format_str.as_str() {
@@ -230,3 +387,196 @@ fn run_cli_mode(
         }
     }
 }
+
+// None means auto-detect.
+fn parse_game(game: Option<&str>) -> Option<GameVariant> {
+    match game {
+        Some("freefire") | Some("ff") => Some(GameVariant::FreeFire),
+        Some("max") => Some(GameVariant::FreeFireMax),
+        Some("tela") => Some(GameVariant::FreeFireTela),
+        Some("auto") | None => None,
+        _ => {
+            eprintln!("Invalid game variant. Using Free Fire standard.");
+            Some(GameVariant::FreeFire)
+        }
+    }
+}
+
+fn load_targets_or_exit(name: &str) -> TargetConfig {
+    let loaded = load_named_target_set(name).and_then(|set| {
+        println!("Using target set: {}", set.name);
+        resolve_target_set(set)
+    });
+    match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_batch(
+    inputs: Vec<String>,
+    game: Option<String>,
+    targets: Option<String>,
+    threads: Option<usize>,
+    summary_only: bool,
+) {
+    let files = match expand_inputs(&inputs) {
+        Ok(files) if files.is_empty() => {
+            eprintln!("Error: no files match {}", inputs.join(", "));
+            std::process::exit(1);
+        }
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let threads = threads
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1);
+
+    let game_variant = parse_game(game.as_deref());
+    let fixed_config = targets.map(|name| load_targets_or_exit(&name));
+    if let Some(config) = &fixed_config {
+        for issue in lint_config(config).iter().filter(|i| i.is_error()) {
+            eprintln!("Invalid target {}", issue);
+        }
+    }
+
+    println!("Scanning {} files on {} threads...", files.len(), threads.min(files.len()));
+    let entries = batch_scan(&files, threads, |path| {
+        let input = Input::read(path, None)?;
+        // Without an explicit game or target set, each file picks the
+        // built-in set for the variant it looks like.
+        let variant = game_variant.unwrap_or_else(|| match fixed_config {
+            Some(_) => GameVariant::FreeFire,
+            None => detect_game_variant_in(&input)
+                .ok()
+                .flatten()
+                .unwrap_or(GameVariant::FreeFire),
+        });
+        let results = match &fixed_config {
+            Some(config) => scan_input(path, &input, config)?,
+            None => scan_input(path, &input, &get_game_config(variant))?,
+        };
+        Ok((variant, results))
+    });
+
+    if !summary_only {
+        for entry in &entries {
+            println!("\n{}", entry.path);
+            match &entry.result {
+                Ok((variant, results)) => print_results(results, *variant, false),
+                Err(e) => print_error(e),
+            }
+        }
+    }
+
+    print_batch_summary(&entries);
+
+    if entries.iter().any(|entry| entry.result.is_err()) {
+        std::process::exit(1);
+    }
+}
+
+// Directories expand to the files directly inside them and glob patterns to
+// the files they match, each sorted by path; anything else is taken as-is.
+fn expand_inputs(inputs: &[String]) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = PathBuf::from(input);
+        let mut matched: Vec<PathBuf> = if path.is_dir() {
+            std::fs::read_dir(&path)
+                .map_err(|e| format!("{}: {}", input, e))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file())
+                .collect()
+        } else if input.contains(['*', '?', '[']) {
+            glob::glob(input)
+                .map_err(|e| format!("invalid pattern '{}': {}", input, e))?
+                .filter_map(Result::ok)
+                .filter(|path| path.is_file())
+                .collect()
+        } else {
+            vec![path]
+        };
+        matched.sort();
+
+        for path in matched {
+            let path = path.to_string_lossy().into_owned();
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn run_lint(targets: Vec<String>) {
+    let names: Vec<String> = if targets.is_empty() {
+        builtin_target_set_names().map(str::to_string).collect()
+    } else {
+        targets
+    };
+
+    let mut error_count = 0;
+    for name in &names {
+        let issues = match load_named_target_set(name).and_then(|set| {
+            let mut issues = lint_target_set(&set);
+            issues.extend(lint_config(&resolve_target_set(set)?));
+            Ok(issues)
+        }) {
+            Ok(issues) => issues,
+            Err(e) => {
+                print_error(&format!("{}: {}", name, e));
+                error_count += 1;
+                continue;
+            }
+        };
+
+        error_count += issues.iter().filter(|i| i.is_error()).count();
+        print_lint_report(name, &issues);
+    }
+
+    if error_count > 0 {
+        print_error(&format!("{} error(s) found", error_count));
+        std::process::exit(1);
+    }
+    print_success("All target sets passed lint");
+}
+
+fn run_remap(
+    old: &Path,
+    new: &Path,
+    targets: Option<String>,
+    output: Option<PathBuf>,
+    min_confidence: f32,
+) -> Result<(), String> {
+    let read_dump = |path: &Path| -> Result<_, String> {
+        let input = Input::read(&path.to_string_lossy(), None)?;
+        parse_dump(&mut input.lines()?).map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    print_info(&format!("Comparing {} with {}...", old.display(), new.display()));
+    let renames = remap(&read_dump(old)?.classes, &read_dump(new)?.classes);
+    print_renames(&renames, min_confidence);
+
+    let (Some(targets), Some(output)) = (targets, output) else {
+        return Ok(());
+    };
+    let mut set = load_named_target_set(&targets)?;
+    let (changes, warnings) = apply_renames(&mut set, &renames, min_confidence);
+    print_target_changes(&changes, &warnings);
+
+    let json = output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let text = if json {
+        serde_json::to_string_pretty(&set).map_err(|e| e.to_string())?
+    } else {
+        toml::to_string_pretty(&set).map_err(|e| e.to_string())?
+    };
+    fs::write(&output, text).map_err(|e| format!("Error writing {}: {}", output.display(), e))?;
+    print_success(&format!("Wrote {} ({} change(s))", output.display(), changes.len()));
+    Ok(())
+}
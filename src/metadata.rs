@@ -0,0 +1,391 @@
+// Reader for IL2CPP's global-metadata.dat, versions 24 and 27 to 29: the string
+// table and the image, type, field, method and parameter definitions.
+// What the runtime computes (field offsets, type signatures, method
+// addresses) lives in the binary instead; il2cpp.rs pairs the two.
+
+use std::ops::Range;
+
+pub const METADATA_SANITY: u32 = 0xFAB1_1BAF;
+
+// Header entries are (offset, size) pairs in this order from version 24 on;
+// images move up one slot once rgctx entries leave the file in 24.2.
+const STRINGS: usize = 2;
+const METHODS: usize = 5;
+const PARAMETERS: usize = 10;
+const FIELDS: usize = 11;
+const GENERIC_PARAMETERS: usize = 12;
+const GENERIC_CONTAINERS: usize = 14;
+const INTERFACES: usize = 16;
+const TYPE_DEFINITIONS: usize = 19;
+
+// Record layouts changed between Unity releases without the header always
+// saying so (24.0 to 24.5 all report 24), so the revision is inferred from
+// which layout the section sizes fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Revision {
+    V24_0,
+    V24_1,
+    V24_2,
+    V27,
+}
+
+impl Revision {
+    pub fn name(&self) -> &str {
+        match self {
+            Revision::V24_0 => "24.0",
+            Revision::V24_1 => "24.1",
+            Revision::V24_2 => "24.2",
+            Revision::V27 => "27",
+        }
+    }
+
+    fn type_size(self) -> usize {
+        match self {
+            Revision::V24_0 => 104,
+            Revision::V24_1 => 100,
+            Revision::V24_2 => 92,
+            Revision::V27 => 88,
+        }
+    }
+
+    fn method_size(self) -> usize {
+        match self {
+            Revision::V24_0 => 56,
+            Revision::V24_1 => 52,
+            _ => 32,
+        }
+    }
+
+    // Fields and parameters lose their custom attribute index in 24.1.
+    fn member_size(self) -> usize {
+        if self == Revision::V24_0 {
+            16
+        } else {
+            12
+        }
+    }
+
+    fn image_size(self) -> usize {
+        if self == Revision::V24_0 {
+            32
+        } else {
+            40
+        }
+    }
+
+    fn images_entry(self) -> usize {
+        if self <= Revision::V24_1 {
+            21
+        } else {
+            20
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageDefinition {
+    pub name_index: u32,
+    pub type_start: u32,
+    pub type_count: u32,
+}
+
+impl ImageDefinition {
+    // Indices of the image's types; `parse` has checked they are in range.
+    pub fn types(&self) -> Range<usize> {
+        self.type_start as usize..self.type_start as usize + self.type_count as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeDefinition {
+    pub name_index: u32,
+    pub namespace_index: u32,
+    // Indices into the binary's type table, or -1.
+    pub declaring_type: i32,
+    pub parent: i32,
+    pub generic_container: i32,
+    pub flags: u32,
+    pub field_start: u32,
+    pub method_start: u32,
+    pub interfaces_start: u32,
+    pub field_count: u16,
+    pub method_count: u16,
+    pub interfaces_count: u16,
+    pub bitfield: u32,
+}
+
+impl TypeDefinition {
+    pub fn is_value_type(&self) -> bool {
+        self.bitfield & 0x1 != 0
+    }
+
+    pub fn is_enum(&self) -> bool {
+        self.bitfield & 0x2 != 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldDefinition {
+    pub name_index: u32,
+    pub type_index: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodDefinition {
+    pub name_index: u32,
+    pub return_type: i32,
+    pub parameter_start: u32,
+    // Index into the global method pointer table; only before 24.2.
+    pub method_index: i32,
+    pub token: u32,
+    pub flags: u16,
+    pub slot: u16,
+    pub parameter_count: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParameterDefinition {
+    pub name_index: u32,
+    pub type_index: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericContainer {
+    pub type_argc: u32,
+    pub parameter_start: u32,
+}
+
+pub struct Metadata<'a> {
+    pub version: u32,
+    pub revision: Revision,
+    strings: &'a [u8],
+    pub images: Vec<ImageDefinition>,
+    pub types: Vec<TypeDefinition>,
+    pub fields: Vec<FieldDefinition>,
+    pub methods: Vec<MethodDefinition>,
+    pub parameters: Vec<ParameterDefinition>,
+    pub generic_containers: Vec<GenericContainer>,
+    // Name index of each generic parameter.
+    pub generic_parameters: Vec<u32>,
+    pub interfaces: Vec<i32>,
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn i32_at(bytes: &[u8], offset: usize) -> i32 {
+    u32_at(bytes, offset) as i32
+}
+
+struct Header<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Header<'a> {
+    fn entry(&self, index: usize) -> Result<&'a [u8], String> {
+        let at = 8 + index * 8;
+        let (offset, size) = match self.bytes.get(at..at + 8) {
+            Some(pair) => (u32_at(pair, 0) as usize, u32_at(pair, 4) as usize),
+            None => return Err("metadata header is truncated".to_string()),
+        };
+        self.bytes
+            .get(offset..offset.saturating_add(size))
+            .ok_or_else(|| format!("metadata section {} runs past the end of the file", index))
+    }
+
+    // Splits section `index` into `size`-byte records.
+    fn records(&self, index: usize, size: usize) -> Result<Vec<&'a [u8]>, String> {
+        let section = self.entry(index)?;
+        if section.len() % size != 0 {
+            return Err(format!(
+                "metadata section {} is {} bytes, not a multiple of {}",
+                index,
+                section.len(),
+                size
+            ));
+        }
+        Ok(section.chunks_exact(size).collect())
+    }
+}
+
+impl<'a> Metadata<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        if bytes.len() < 8 || u32_at(bytes, 0) != METADATA_SANITY {
+            return Err("not an IL2CPP global-metadata.dat file".to_string());
+        }
+        let version = u32_at(bytes, 4);
+        let header = Header { bytes };
+
+        let candidates: &[Revision] = match version {
+            24 => &[Revision::V24_2, Revision::V24_1, Revision::V24_0],
+            27..=29 => &[Revision::V27],
+            _ => {
+                return Err(format!(
+                    "unsupported metadata version {} (24 and 27 to 29 are supported)",
+                    version
+                ))
+            }
+        };
+        let mut last_error = String::new();
+        for &revision in candidates {
+            match Self::parse_as(&header, version, revision) {
+                Ok(metadata) => return Ok(metadata),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(format!("metadata version {} does not parse: {}", version, last_error))
+    }
+
+    fn parse_as(header: &Header<'a>, version: u32, revision: Revision) -> Result<Self, String> {
+        let types: Vec<TypeDefinition> = header
+            .records(TYPE_DEFINITIONS, revision.type_size())?
+            .into_iter()
+            .map(|record| read_type(record, revision))
+            .collect();
+        let fields: Vec<FieldDefinition> = header
+            .records(FIELDS, revision.member_size())?
+            .into_iter()
+            .map(|record| FieldDefinition {
+                name_index: u32_at(record, 0),
+                type_index: i32_at(record, 4),
+            })
+            .collect();
+        let methods: Vec<MethodDefinition> = header
+            .records(METHODS, revision.method_size())?
+            .into_iter()
+            .map(|record| read_method(record, revision))
+            .collect();
+        let parameters = header
+            .records(PARAMETERS, revision.member_size())?
+            .into_iter()
+            .map(|record| ParameterDefinition {
+                name_index: u32_at(record, 0),
+                // After the name and token, and the attribute index in 24.0.
+                type_index: i32_at(record, revision.member_size() - 4),
+            })
+            .collect();
+        let images: Vec<ImageDefinition> = header
+            .records(revision.images_entry(), revision.image_size())?
+            .into_iter()
+            .map(|record| ImageDefinition {
+                name_index: u32_at(record, 0),
+                type_start: u32_at(record, 8),
+                type_count: u32_at(record, 12),
+            })
+            .collect();
+
+        // A layout that does not fit leaves ranges pointing past the tables.
+        let fits = |start: u32, count: usize, len: usize| {
+            count == 0 || (start as usize).checked_add(count).is_some_and(|end| end <= len)
+        };
+        for definition in &types {
+            if !fits(definition.field_start, definition.field_count as usize, fields.len())
+                || !fits(definition.method_start, definition.method_count as usize, methods.len())
+            {
+                return Err(format!("type ranges do not fit a {} layout", revision.name()));
+            }
+        }
+        if images.iter().any(|image| !fits(image.type_start, image.type_count as usize, types.len())) {
+            return Err(format!("image ranges do not fit a {} layout", revision.name()));
+        }
+
+        Ok(Metadata {
+            version,
+            revision,
+            strings: header.entry(STRINGS)?,
+            images,
+            types,
+            fields,
+            methods,
+            parameters,
+            generic_containers: header
+                .records(GENERIC_CONTAINERS, 16)?
+                .into_iter()
+                .map(|record| GenericContainer {
+                    type_argc: u32_at(record, 4),
+                    parameter_start: u32_at(record, 12),
+                })
+                .collect(),
+            generic_parameters: header
+                .records(GENERIC_PARAMETERS, 16)?
+                .into_iter()
+                .map(|record| u32_at(record, 4))
+                .collect(),
+            interfaces: header
+                .records(INTERFACES, 4)?
+                .into_iter()
+                .map(|record| i32_at(record, 0))
+                .collect(),
+        })
+    }
+
+    pub fn string(&self, index: u32) -> &'a str {
+        let tail = self.strings.get(index as usize..).unwrap_or_default();
+        let end = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+        std::str::from_utf8(&tail[..end]).unwrap_or("")
+    }
+}
+
+fn read_type(record: &[u8], revision: Revision) -> TypeDefinition {
+    // Optional fields before `flags`: the custom attribute index (24.0),
+    // the by-ref type (before 27) and the rgctx range (before 24.2).
+    let mut at = 8;
+    if revision == Revision::V24_0 {
+        at += 4;
+    }
+    at += 4; // byval type
+    if revision < Revision::V27 {
+        at += 4;
+    }
+    let declaring_type = i32_at(record, at);
+    let parent = i32_at(record, at + 4);
+    at += 12; // declaring type, parent, element type
+    if revision <= Revision::V24_1 {
+        at += 8;
+    }
+    let generic_container = i32_at(record, at);
+    let flags = u32_at(record, at + 4);
+    let starts = at + 8;
+    let counts = starts + 32;
+
+    TypeDefinition {
+        name_index: u32_at(record, 0),
+        namespace_index: u32_at(record, 4),
+        declaring_type,
+        parent,
+        generic_container,
+        flags,
+        field_start: u32_at(record, starts),
+        method_start: u32_at(record, starts + 4),
+        interfaces_start: u32_at(record, starts + 20),
+        method_count: u16_at(record, counts),
+        field_count: u16_at(record, counts + 4),
+        interfaces_count: u16_at(record, counts + 12),
+        bitfield: u32_at(record, counts + 16),
+    }
+}
+
+fn read_method(record: &[u8], revision: Revision) -> MethodDefinition {
+    // Before 24.2 the method and invoker indices, delegate wrapper and rgctx
+    // range sit between the generic container and the token.
+    let (method_index, token_at) = match revision {
+        Revision::V24_0 => (i32_at(record, 24), 44),
+        Revision::V24_1 => (i32_at(record, 20), 40),
+        _ => (-1, 20),
+    };
+    MethodDefinition {
+        name_index: u32_at(record, 0),
+        return_type: i32_at(record, 8),
+        parameter_start: u32_at(record, 12),
+        method_index,
+        token: u32_at(record, token_at),
+        flags: u16_at(record, token_at + 4),
+        slot: u16_at(record, token_at + 8),
+        parameter_count: u16_at(record, token_at + 10),
+    }
+}
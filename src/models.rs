@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GameVariant {
     FreeFire,
@@ -17,73 +19,397 @@ impl GameVariant {
     }
 }
 
+// Serialized with a `kind` tag, which is how target files spell them:
+// `kind = "field", name = "...", class = "...", field = { ... }`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum Target {
     Fixed { name: String, hex: String },
     Pattern { name: String, pattern: String },
     Regex { name: String, pattern: String },
-    AoB { name: String, signature: String },
+    // Searched in executable code unless `section` names a section to use
+    // instead (e.g. ".rodata"). `operand` turns the match into the address
+    // it references; `deref` then follows pointers stored in the input.
+    // Anything other than `expect` matches (default one) is a failure.
+    #[serde(rename = "aob")]
+    AoB {
+        name: String,
+        signature: String,
+        #[serde(default = "default_expect", skip_serializing_if = "is_default_expect")]
+        expect: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        section: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        operand: Option<Operand>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deref: Vec<u64>,
+    },
+    // `class` is a regex matched against the simple or namespace-qualified
+    // class name. Only instance fields are considered.
+    Field { name: String, class: String, field: FieldSelector },
+    // Like Field, but only static fields are considered, so `index` and
+    // `anchor` count within the class's static storage.
+    StaticField { name: String, class: String, field: FieldSelector },
+    // A method of the classes `class` matches, returning the address
+    // component picked by `method.component`.
+    Method { name: String, class: String, method: MethodSelector },
+    // The `// TypeDefIndex: N` of the class `class` matches.
+    TypeIndex { name: String, class: String },
+    // Evaluated after every other target, e.g. "${Camera} + 0x24".
+    Expr { name: String, expr: String },
     Separator,
 }
 
-impl Target {
-    pub fn new_fixed(name: &str, hex: &str) -> Self {
-        Target::Fixed {
-            name: name.to_string(),
-            hex: hex.to_string(),
+fn default_expect() -> usize {
+    1
+}
+
+fn is_default_expect(expect: &usize) -> bool {
+    *expect == 1
+}
+
+// Picks a field inside a class. Type, name and anchor are regexes that must
+// match the whole field type / field name; an unset part matches anything.
+// `modifiers` are keywords such as "protected" the field must all have.
+// `index` selects the Nth matching field (0-based), counted from the anchor
+// when one is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldSelector {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<String>,
+    pub type_name: Option<String>,
+    pub name: Option<String>,
+    pub index: usize,
+    pub anchor: Option<FieldAnchor>,
+}
+
+// Picks a method inside a class. Name and return type are regexes that must
+// match the whole name / type; `params`, when set, must match the parameter
+// types one for one. `index` selects among the matches; if that method lacks
+// the requested component (abstract ones have no RVA) the target fails.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MethodSelector {
+    pub name: Option<String>,
+    pub return_type: Option<String>,
+    pub params: Option<Vec<String>>,
+    pub index: usize,
+    pub component: MethodComponent,
+}
+
+// Which of the addresses in a method's `// RVA: ... Offset: ... VA: ...
+// Slot: ...` comment a method target returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MethodComponent {
+    #[default]
+    Rva,
+    Offset,
+    Va,
+    Slot,
+}
+
+impl MethodComponent {
+    pub fn name(&self) -> &str {
+        match self {
+            MethodComponent::Rva => "RVA",
+            MethodComponent::Offset => "offset",
+            MethodComponent::Va => "VA",
+            MethodComponent::Slot => "slot",
         }
     }
 
-    pub fn new_pattern(name: &str, pattern: &str) -> Self {
-        Target::Pattern {
-            name: name.to_string(),
-            pattern: pattern.to_string(),
+    pub fn value(&self, method: &MethodDef) -> Option<u64> {
+        match self {
+            MethodComponent::Rva => method.rva,
+            MethodComponent::Offset => method.offset,
+            MethodComponent::Va => method.va,
+            MethodComponent::Slot => method.slot.map(u64::from),
         }
     }
+}
 
-    pub fn new_regex(name: &str, pattern: &str) -> Self {
-        Target::Regex {
-            name: name.to_string(),
-            pattern: pattern.to_string(),
-        }
+// Where an AoB match encodes the value it is after: `size` little-endian
+// bytes starting `offset` bytes into the match. With `instruction_length`
+// the value is a signed displacement from the end of the instruction
+// (x86-64 RIP-relative addressing); without it the value is used as is.
+// The AArch64 encodings decode the instruction at `offset` instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operand {
+    pub offset: usize,
+    #[serde(default = "Operand::default_size")]
+    pub size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "OperandEncoding::is_value")]
+    pub encoding: OperandEncoding,
+}
+
+impl Operand {
+    fn default_size() -> usize {
+        4
     }
+}
 
-    pub fn new_aob(name: &str, signature: &str) -> Self {
-        Target::AoB {
-            name: name.to_string(),
-            signature: signature.to_string(),
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperandEncoding {
+    #[default]
+    Value,
+    // ADRP (or ADR) plus the ADD/LDR that completes the address.
+    Adrp,
+    // B or BL; resolves to the branch target.
+    Branch,
+}
+
+impl OperandEncoding {
+    fn is_value(&self) -> bool {
+        *self == OperandEncoding::Value
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldAnchor {
+    After(String),
+    Before(String),
+}
+
+impl Target {
+    pub fn kind(&self) -> Option<TargetKind> {
+        match self {
+            Target::Fixed { .. } => Some(TargetKind::Fixed),
+            Target::Pattern { .. } => Some(TargetKind::Pattern),
+            Target::Regex { .. } => Some(TargetKind::Regex),
+            Target::AoB { .. } => Some(TargetKind::AoB),
+            Target::Field { .. } => Some(TargetKind::Field),
+            Target::StaticField { .. } => Some(TargetKind::StaticField),
+            Target::Method { .. } => Some(TargetKind::Method),
+            Target::TypeIndex { .. } => Some(TargetKind::TypeIndex),
+            Target::Expr { .. } => Some(TargetKind::Expr),
+            Target::Separator => None,
         }
     }
 
-    #[allow(dead_code)]
     pub fn name(&self) -> Option<&str> {
         match self {
             Target::Fixed { name, .. } => Some(name),
             Target::Pattern { name, .. } => Some(name),
             Target::Regex { name, .. } => Some(name),
             Target::AoB { name, .. } => Some(name),
+            Target::Field { name, .. } => Some(name),
+            Target::StaticField { name, .. } => Some(name),
+            Target::Method { name, .. } => Some(name),
+            Target::TypeIndex { name, .. } => Some(name),
+            Target::Expr { name, .. } => Some(name),
             Target::Separator => None,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetKind {
+    Fixed,
+    Pattern,
+    Regex,
+    AoB,
+    Field,
+    StaticField,
+    Method,
+    TypeIndex,
+    Expr,
+}
+
+impl TargetKind {
+    pub fn name(&self) -> &str {
+        match self {
+            TargetKind::Fixed => "fixed",
+            TargetKind::Pattern => "pattern",
+            TargetKind::Regex => "regex",
+            TargetKind::AoB => "aob",
+            TargetKind::Field => "field",
+            TargetKind::StaticField => "static_field",
+            TargetKind::Method => "method",
+            TargetKind::TypeIndex => "type_index",
+            TargetKind::Expr => "expr",
+        }
+    }
+}
+
+// A resolved offset. `text` keeps the spelling it was read with for display;
+// exporters write `value`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OffsetValue {
+    pub value: u64,
+    pub text: String,
+}
+
+impl OffsetValue {
+    pub fn new(value: u64) -> Self {
+        OffsetValue {
+            value,
+            text: format!("0x{:X}", value),
+        }
+    }
+
+    // Accepts "0x4C" and "0X4c". The prefix is required: expressions read
+    // bare numbers as decimal, so a bare "24" would mean two things.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let trimmed = text.trim();
+        let Some(digits) = trimmed.strip_prefix("0x").or_else(|| trimmed.strip_prefix("0X")) else {
+            return Err(match u64::from_str_radix(trimmed, 16) {
+                Ok(_) => format!("'{}' needs a 0x prefix (write 0x{} if it is hex)", text, trimmed),
+                Err(_) => format!("'{}' is not a hex offset", text),
+            });
+        };
+        u64::from_str_radix(digits, 16)
+            .map(|value| OffsetValue {
+                value,
+                text: trimmed.to_string(),
+            })
+            .map_err(|_| format!("'{}' is not a hex offset", text))
+    }
+
+    pub fn hex(&self) -> String {
+        format!("0x{:X}", self.value)
+    }
+}
+
+impl PartialEq for OffsetValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for OffsetValue {}
+
+impl std::fmt::Display for OffsetValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OffsetResult {
     pub name: String,
-    pub offset: Option<String>,
+    pub offset: Option<OffsetValue>,
     pub category: String,
+    pub candidates: Vec<Candidate>,
+    // How many matches were found; only the first few are kept as candidates.
+    #[serde(skip)]
+    pub match_count: usize,
+    pub ambiguous: bool,
+    pub provenance: Provenance,
+    pub error: Option<String>,
+    // Time spent searching for this target.
+    #[serde(skip)]
+    pub elapsed: Duration,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl OffsetResult {
+    pub fn new(name: &str, category: &str, provenance: Provenance) -> Self {
+        OffsetResult {
+            name: name.to_string(),
+            offset: None,
+            category: category.to_string(),
+            candidates: Vec::new(),
+            match_count: 0,
+            ambiguous: false,
+            provenance,
+            error: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Location {
+    Line(usize),
+    Byte(u64),
+    // Rows of global-metadata.dat's type, field and method tables, for
+    // classes read from metadata rather than a dump.cs.
+    TypeDef(u32),
+    Field(u32),
+    Method(u32),
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Line(line) => write!(f, "line {}", line),
+            Location::Byte(position) => write!(f, "byte 0x{:X}", position),
+            Location::TypeDef(index) => write!(f, "TypeDefIndex {}", index),
+            Location::Field(index) => write!(f, "field #{}", index),
+            Location::Method(index) => write!(f, "method #{}", index),
+        }
+    }
+}
+
+// One place in the input a target matched. A result is ambiguous when its
+// candidates disagree on the offset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    pub location: Location,
+    pub class: Option<String>,
+    pub text: String,
+    pub offset: OffsetValue,
+}
+
+// Where a result came from. Location, text and class describe the candidate
+// that was picked and stay empty for Fixed targets and misses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub source: String,
+    pub kind: TargetKind,
+    pub location: Option<Location>,
+    pub class: Option<String>,
+    pub text: Option<String>,
+}
+
+impl Provenance {
+    pub fn new(source: &str, kind: TargetKind) -> Self {
+        Provenance {
+            source: source.to_string(),
+            kind,
+            location: None,
+            class: None,
+            text: None,
+        }
+    }
+
+    // Short form for listings, e.g. "field COW.GamePlay.Player @ dump.cs line 17".
+    pub fn summary(&self) -> String {
+        let mut summary = self.kind.name().to_string();
+        if let Some(class) = &self.class {
+            summary.push(' ');
+            summary.push_str(class);
+        }
+        if let Some(location) = &self.location {
+            let file = std::path::Path::new(&self.source)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| self.source.clone());
+            summary.push_str(&format!(" @ {} {}", file, location));
+        }
+        summary
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OffsetCategory {
     Core,
     Player,
     Camera,
     Weapon,
+    #[serde(alias = "Silent Aim")]
     Silent,
     Collision,
     Attributes,
+    #[serde(alias = "Bot Detection")]
     Bot,
+    #[serde(alias = "Skeleton/Bones")]
     Skeleton,
 }
 
@@ -103,6 +429,36 @@ impl OffsetCategory {
     }
 }
 
+// A target file: named groups of targets, loaded from TOML or JSON. A set
+// that `extends` another starts from the parent's targets, drops the names
+// listed in `remove`, and then replaces same-named targets or adds new ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetSet {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<String>,
+    #[serde(default, rename = "category")]
+    pub categories: Vec<TargetGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetGroup {
+    pub name: OffsetCategory,
+    #[serde(default, rename = "target")]
+    pub targets: Vec<TargetDef>,
+}
+
+// JSON has no comments, so every target may carry one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetDef {
+    #[serde(flatten)]
+    pub target: Target,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExportFormat {
     Json,
@@ -121,3 +477,159 @@ impl ExportFormat {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypeKind {
+    Class,
+    Struct,
+    Enum,
+    Interface,
+}
+
+impl TypeKind {
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "class" => Some(TypeKind::Class),
+            "struct" => Some(TypeKind::Struct),
+            "enum" => Some(TypeKind::Enum),
+            "interface" => Some(TypeKind::Interface),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DumpModel {
+    pub classes: Vec<ClassDef>,
+}
+
+impl DumpModel {
+    #[allow(dead_code)]
+    pub fn namespaces(&self) -> Vec<&str> {
+        let mut namespaces: Vec<&str> = self.classes.iter().map(|c| c.namespace.as_str()).collect();
+        namespaces.sort_unstable();
+        namespaces.dedup();
+        namespaces
+    }
+
+    #[allow(dead_code)]
+    pub fn find_class(&self, name: &str) -> Option<&ClassDef> {
+        self.classes
+            .iter()
+            .find(|c| c.name == name || c.full_name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassDef {
+    pub namespace: String,
+    pub name: String,
+    pub kind: TypeKind,
+    pub modifiers: Vec<String>,
+    pub parent: Option<String>,
+    pub interfaces: Vec<String>,
+    pub type_def_index: Option<u32>,
+    pub location: Location,
+    pub fields: Vec<FieldDef>,
+    pub properties: Vec<PropertyDef>,
+    pub methods: Vec<MethodDef>,
+}
+
+impl ClassDef {
+    pub fn full_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+
+    // Members in the order Il2CppDumper writes them: fields, properties, methods.
+    pub fn members(&self) -> impl Iterator<Item = Member<'_>> {
+        self.fields
+            .iter()
+            .map(Member::Field)
+            .chain(self.properties.iter().map(Member::Property))
+            .chain(self.methods.iter().map(Member::Method))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDef {
+    pub modifiers: Vec<String>,
+    pub type_name: String,
+    pub name: String,
+    pub offset: Option<u64>,
+    pub location: Location,
+    pub text: String,
+}
+
+impl FieldDef {
+    pub fn is_static(&self) -> bool {
+        self.modifiers.iter().any(|m| m == "static")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyDef {
+    pub modifiers: Vec<String>,
+    pub type_name: String,
+    pub name: String,
+    pub accessors: Vec<String>,
+    pub location: Location,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParamDef {
+    pub type_name: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodDef {
+    pub modifiers: Vec<String>,
+    pub return_type: String,
+    pub name: String,
+    pub params: Vec<ParamDef>,
+    pub rva: Option<u64>,
+    pub offset: Option<u64>,
+    pub va: Option<u64>,
+    pub slot: Option<u32>,
+    pub location: Location,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Member<'a> {
+    Field(&'a FieldDef),
+    Property(&'a PropertyDef),
+    Method(&'a MethodDef),
+}
+
+impl<'a> Member<'a> {
+    pub fn text(&self) -> &'a str {
+        match self {
+            Member::Field(f) => &f.text,
+            Member::Property(p) => &p.text,
+            Member::Method(m) => &m.text,
+        }
+    }
+
+    pub fn location(&self) -> Location {
+        match self {
+            Member::Field(f) => f.location,
+            Member::Property(p) => p.location,
+            Member::Method(m) => m.location,
+        }
+    }
+
+    // The hex value a text target resolves to: field offset or method RVA.
+    pub fn value(&self) -> Option<u64> {
+        match self {
+            Member::Field(f) => f.offset,
+            Member::Property(_) => None,
+            Member::Method(m) => m.rva,
+        }
+    }
+}
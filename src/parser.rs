@@ -0,0 +1,354 @@
+use crate::input::TextLines;
+use crate::models::{
+    ClassDef, DumpModel, FieldDef, Location, MethodDef, ParamDef, PropertyDef, TypeKind,
+};
+use std::io::BufRead;
+
+const MODIFIERS: &[&str] = &[
+    "public", "private", "protected", "internal", "static", "readonly", "const", "volatile",
+    "new", "unsafe", "fixed", "abstract", "virtual", "override", "sealed", "extern", "async",
+];
+
+const PARAM_MODIFIERS: &[&str] = &["ref", "out", "in", "params", "this"];
+
+// Streams the dump, handing each class to `handle` once it is complete.
+pub fn for_each_class<R: BufRead>(
+    lines: &mut TextLines<R>,
+    mut handle: impl FnMut(ClassDef),
+) -> Result<(), String> {
+    let mut parser = DumpParser::new();
+    let mut line_no = 0;
+
+    while let Some(line) = lines.next_line()? {
+        line_no += 1;
+        if let Some(class) = parser.feed_line(line_no, line) {
+            handle(class);
+        }
+    }
+    if let Some(class) = parser.finish() {
+        handle(class);
+    }
+    Ok(())
+}
+
+pub fn parse_dump<R: BufRead>(lines: &mut TextLines<R>) -> Result<DumpModel, String> {
+    let mut model = DumpModel::default();
+    for_each_class(lines, |class| model.classes.push(class))?;
+    Ok(model)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct MethodAddress {
+    rva: Option<u64>,
+    offset: Option<u64>,
+    va: Option<u64>,
+    slot: Option<u32>,
+}
+
+// Line-at-a-time parser for Il2CppDumper's dump.cs. A class is handed back
+// as soon as its closing brace is seen, so callers can process it and drop it.
+#[derive(Default)]
+pub struct DumpParser {
+    namespace: String,
+    header: Option<ClassDef>,
+    current: Option<ClassDef>,
+    address: Option<MethodAddress>,
+}
+
+impl DumpParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed_line(&mut self, line_no: usize, line: &str) -> Option<ClassDef> {
+        let trimmed = line.trim();
+
+        if self.current.is_none() {
+            if let Some(namespace) = trimmed.strip_prefix("// Namespace:") {
+                self.namespace = namespace.trim().to_string();
+            } else if trimmed == "{" {
+                self.current = self.header.take();
+            } else if !line.starts_with(char::is_whitespace) {
+                if let Some(class) = parse_class_header(trimmed, &self.namespace, line_no) {
+                    self.header = Some(class);
+                }
+            }
+            return None;
+        }
+
+        if line.starts_with('}') {
+            self.address = None;
+            return self.current.take();
+        }
+
+        let class = self.current.as_mut()?;
+
+        if trimmed.is_empty() || trimmed.starts_with('[') {
+            return None;
+        }
+
+        if let Some(comment) = trimmed.strip_prefix("//") {
+            if comment.contains("RVA:") {
+                self.address = Some(parse_method_address(comment));
+            }
+            return None;
+        }
+
+        if trimmed.contains('(') && trimmed.ends_with('}') {
+            if let Some(mut method) = parse_method(trimmed, line_no) {
+                let address = self.address.take().unwrap_or_default();
+                method.rva = address.rva;
+                method.offset = address.offset;
+                method.va = address.va;
+                method.slot = address.slot;
+                class.methods.push(method);
+            }
+        } else if trimmed.ends_with('}') && trimmed.contains('{') {
+            if let Some(property) = parse_property(trimmed, line_no) {
+                class.properties.push(property);
+            }
+        } else if trimmed.contains(';') {
+            if let Some(field) = parse_field(trimmed, line_no) {
+                class.fields.push(field);
+            }
+        }
+
+        None
+    }
+
+    pub fn finish(&mut self) -> Option<ClassDef> {
+        self.current.take()
+    }
+}
+
+fn parse_class_header(line: &str, namespace: &str, line_no: usize) -> Option<ClassDef> {
+    if line.starts_with("//") || line.starts_with('[') {
+        return None;
+    }
+
+    let (decl, comment) = split_comment(line);
+    let type_def_index = comment
+        .and_then(|c| c.trim().strip_prefix("TypeDefIndex:"))
+        .and_then(|index| index.trim().parse().ok());
+
+    let (head, bases) = match decl.split_once(" : ") {
+        Some((head, bases)) => (head, Some(bases)),
+        None => (decl, None),
+    };
+
+    let mut modifiers = Vec::new();
+    let mut rest = head.trim();
+    let kind = loop {
+        let (token, tail) = rest.split_once(' ')?;
+        if let Some(kind) = TypeKind::from_keyword(token) {
+            rest = tail.trim();
+            break kind;
+        }
+        modifiers.push(token.to_string());
+        rest = tail.trim_start();
+    };
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    // dump.cs does not say which base is the class and which are interfaces;
+    // use the .NET naming convention (IFoo) to tell them apart.
+    let mut parent = None;
+    let mut interfaces = Vec::new();
+    for (i, base) in bases.map(split_top_level).unwrap_or_default().into_iter().enumerate() {
+        if i == 0 && kind == TypeKind::Class && !looks_like_interface(&base) {
+            parent = Some(base);
+        } else {
+            interfaces.push(base);
+        }
+    }
+
+    Some(ClassDef {
+        namespace: namespace.to_string(),
+        name: rest.to_string(),
+        kind,
+        modifiers,
+        parent,
+        interfaces,
+        type_def_index,
+        location: Location::Line(line_no),
+        fields: Vec::new(),
+        properties: Vec::new(),
+        methods: Vec::new(),
+    })
+}
+
+fn looks_like_interface(name: &str) -> bool {
+    let mut chars = name.rsplit('.').next().unwrap_or(name).chars();
+    chars.next() == Some('I') && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+}
+
+fn parse_method_address(comment: &str) -> MethodAddress {
+    let mut address = MethodAddress::default();
+    let mut tokens = comment.split_whitespace();
+
+    while let Some(key) = tokens.next() {
+        let Some(value) = tokens.next() else { break };
+        match key {
+            "RVA:" => address.rva = parse_hex(value),
+            "Offset:" => address.offset = parse_hex(value),
+            "VA:" => address.va = parse_hex(value),
+            "Slot:" => address.slot = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    address
+}
+
+fn parse_field(line: &str, line_no: usize) -> Option<FieldDef> {
+    let (decl, comment) = split_comment(line);
+    let decl = decl.trim_end().strip_suffix(';')?;
+    let decl = decl.split_once(" = ").map_or(decl, |(lhs, _)| lhs);
+
+    let (modifiers, rest) = take_modifiers(decl, MODIFIERS);
+    let (type_name, name) = split_type_and_name(rest)?;
+
+    Some(FieldDef {
+        modifiers,
+        type_name,
+        name,
+        offset: comment.and_then(|c| parse_hex(c.trim())),
+        location: Location::Line(line_no),
+        text: line.to_string(),
+    })
+}
+
+fn parse_property(line: &str, line_no: usize) -> Option<PropertyDef> {
+    let (decl, body) = line.split_once('{')?;
+    let (modifiers, rest) = take_modifiers(decl, MODIFIERS);
+    let (type_name, name) = split_type_and_name(rest)?;
+
+    let accessors = body
+        .trim_end_matches('}')
+        .split(';')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    Some(PropertyDef {
+        modifiers,
+        type_name,
+        name,
+        accessors,
+        location: Location::Line(line_no),
+        text: line.to_string(),
+    })
+}
+
+fn parse_method(line: &str, line_no: usize) -> Option<MethodDef> {
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    if close < open {
+        return None;
+    }
+
+    let (modifiers, rest) = take_modifiers(&line[..open], MODIFIERS);
+    let (return_type, name) = split_type_and_name(rest)?;
+
+    let params = split_top_level(&line[open + 1..close])
+        .into_iter()
+        .filter_map(|param| {
+            let param = param.split_once(" = ").map_or(param.as_str(), |(lhs, _)| lhs);
+            let (_, rest) = take_modifiers(param, PARAM_MODIFIERS);
+            let (type_name, name) = split_type_and_name(rest)?;
+            Some(ParamDef { type_name, name })
+        })
+        .collect();
+
+    Some(MethodDef {
+        modifiers,
+        return_type,
+        name,
+        params,
+        rva: None,
+        offset: None,
+        va: None,
+        slot: None,
+        location: Location::Line(line_no),
+        text: line.to_string(),
+    })
+}
+
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    match line.split_once("//") {
+        Some((decl, comment)) => (decl.trim_end(), Some(comment)),
+        None => (line, None),
+    }
+}
+
+fn take_modifiers<'a>(decl: &'a str, keywords: &[&str]) -> (Vec<String>, &'a str) {
+    let mut modifiers = Vec::new();
+    let mut rest = decl.trim();
+
+    while let Some((token, tail)) = rest.split_once(' ') {
+        if !keywords.contains(&token) {
+            break;
+        }
+        modifiers.push(token.to_string());
+        rest = tail.trim_start();
+    }
+
+    (modifiers, rest)
+}
+
+// Splits "Dictionary<int, string> m_Names" at the last space outside of
+// generic brackets.
+fn split_type_and_name(decl: &str) -> Option<(String, String)> {
+    let decl = decl.trim();
+    let mut depth = 0i32;
+    let mut split_at = None;
+
+    for (i, c) in decl.char_indices() {
+        match c {
+            '<' | '[' | '(' => depth += 1,
+            '>' | ']' | ')' => depth -= 1,
+            ' ' if depth == 0 => split_at = Some(i),
+            _ => {}
+        }
+    }
+
+    let split_at = split_at?;
+    let type_name = decl[..split_at].trim();
+    let name = decl[split_at + 1..].trim();
+    if type_name.is_empty() || name.is_empty() {
+        return None;
+    }
+    Some((type_name.to_string(), name.to_string()))
+}
+
+fn split_top_level(list: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+
+    for (i, c) in list.char_indices() {
+        match c {
+            '<' | '[' | '(' => depth += 1,
+            '>' | ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(list[start..i].trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last = list[start..].trim();
+    if !last.is_empty() {
+        items.push(last.to_string());
+    }
+    items
+}
+
+pub fn parse_hex(text: &str) -> Option<u64> {
+    let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"))?;
+    u64::from_str_radix(digits, 16).ok()
+}
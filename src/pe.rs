@@ -0,0 +1,116 @@
+// Minimal PE/COFF reader for DLL/EXE inputs: image base, alignment and the
+// section table, which is all that is needed to turn file offsets into RVAs.
+
+const PE32_MAGIC: u16 = 0x10B;
+const PE32_PLUS_MAGIC: u16 = 0x20B;
+const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+#[derive(Debug, Clone)]
+pub struct PeFile {
+    pub is_64: bool,
+    pub image_base: u64,
+    pub sections: Vec<PeSection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PeSection {
+    pub name: String,
+    pub rva: u64,
+    // Where the loader reads the section from, after alignment fix-ups.
+    pub file_offset: u64,
+    pub file_size: u64,
+    pub characteristics: u32,
+}
+
+impl PeSection {
+    pub fn is_executable(&self) -> bool {
+        self.characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0
+    }
+}
+
+fn read<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], String> {
+    offset
+        .checked_add(N)
+        .and_then(|end| bytes.get(offset..end))
+        .map(|bytes| bytes.try_into().unwrap())
+        .ok_or_else(|| format!("PE truncated at byte 0x{:X}", offset))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    read(bytes, offset).map(u16::from_le_bytes)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    read(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    read(bytes, offset).map(u64::from_le_bytes)
+}
+
+fn align_up(value: u64, alignment: u64) -> u64 {
+    if alignment == 0 {
+        value
+    } else {
+        value.div_ceil(alignment) * alignment
+    }
+}
+
+pub fn parse_pe(bytes: &[u8]) -> Result<PeFile, String> {
+    if !bytes.starts_with(b"MZ") {
+        return Err("not a PE file (missing MZ header)".to_string());
+    }
+    let pe_offset = u32_at(bytes, 0x3C)? as usize;
+    if read::<4>(bytes, pe_offset)? != *b"PE\0\0" {
+        return Err(format!("no PE signature at byte 0x{:X}", pe_offset));
+    }
+
+    let coff = pe_offset + 4;
+    let section_count = u16_at(bytes, coff + 2)? as usize;
+    let optional_size = u16_at(bytes, coff + 16)? as usize;
+    let optional = coff + 20;
+
+    let (is_64, image_base) = match u16_at(bytes, optional)? {
+        PE32_MAGIC => (false, u32_at(bytes, optional + 28)? as u64),
+        PE32_PLUS_MAGIC => (true, u64_at(bytes, optional + 24)?),
+        magic => return Err(format!("unknown PE optional header magic 0x{:X}", magic)),
+    };
+    let section_alignment = u32_at(bytes, optional + 32)? as u64;
+    let file_alignment = u32_at(bytes, optional + 36)? as u64;
+
+    let mut sections = Vec::new();
+    for i in 0..section_count {
+        let header = optional + optional_size + i * 40;
+        let raw_name = read::<8>(bytes, header)?;
+        let name_len = raw_name.iter().position(|&b| b == 0).unwrap_or(8);
+        let virtual_size = u32_at(bytes, header + 8)? as u64;
+        let raw_size = u32_at(bytes, header + 16)? as u64;
+        let mut raw_pointer = u32_at(bytes, header + 20)? as u64;
+
+        // The loader rounds the raw pointer down to 512 bytes and never maps
+        // more than the aligned virtual size, whatever the header claims.
+        if file_alignment >= 0x200 {
+            raw_pointer &= !0x1FF;
+        }
+        let mut file_size = align_up(raw_size, file_alignment);
+        if virtual_size != 0 {
+            file_size = file_size.min(align_up(virtual_size, section_alignment));
+        }
+        file_size = file_size.min((bytes.len() as u64).saturating_sub(raw_pointer));
+
+        sections.push(PeSection {
+            name: String::from_utf8_lossy(&raw_name[..name_len]).into_owned(),
+            rva: u32_at(bytes, header + 12)? as u64,
+            file_offset: raw_pointer,
+            file_size,
+            characteristics: u32_at(bytes, header + 36)?,
+        });
+    }
+
+    Ok(PeFile {
+        is_64,
+        image_base,
+        sections,
+    })
+}
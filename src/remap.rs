@@ -0,0 +1,533 @@
+// Carries obfuscated names (HHCBNAPCKHF and the like) across a game update.
+// The obfuscator renames every such identifier each release, so classes of
+// the old and new dump.cs are paired by shape instead: parent, field type
+// sequence, method signatures and the plain (unobfuscated) member names they
+// keep. Fields and methods of paired classes are then lined up by type and
+// position. Every rename carries a confidence between 0 and 1.
+
+use crate::models::{ClassDef, FieldAnchor, Target, TargetSet, TypeKind};
+use regex::{Captures, Regex};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+// Pairs scoring below this are not considered the same class.
+const MIN_CLASS_SCORE: f32 = 0.5;
+// A runner-up within this margin of the chosen pair makes the pair a guess,
+// which halves its confidence.
+const TIE_MARGIN: f32 = 0.05;
+// Members whose (type, position) group changed size are less certain.
+const GROUP_RESIZED_FACTOR: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameKind {
+    Class,
+    Field,
+    Method,
+}
+
+impl RenameKind {
+    pub fn name(&self) -> &str {
+        match self {
+            RenameKind::Class => "class",
+            RenameKind::Field => "field",
+            RenameKind::Method => "method",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rename {
+    pub kind: RenameKind,
+    // The class renamed, or the one declaring the member, in the new dump.
+    pub class: String,
+    pub old: String,
+    pub new: String,
+    pub confidence: f32,
+}
+
+// A rewritten string in a target set.
+pub struct TargetChange {
+    pub target: String,
+    pub before: String,
+    pub after: String,
+}
+
+// Beebyte-style names: a run of capital letters with no other characters.
+pub fn is_obfuscated(name: &str) -> bool {
+    name.len() >= 8 && name.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+fn identifier_re() -> Regex {
+    Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap()
+}
+
+// "Outer.HHCBNAPCKHF<T>" -> "HHCBNAPCKHF"
+fn simple_name(class: &ClassDef) -> &str {
+    let name = class.name.split('<').next().unwrap_or(&class.name);
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+fn outer_name(class: &ClassDef) -> &str {
+    let name = class.name.split('<').next().unwrap_or(&class.name);
+    name.rsplit_once('.').map_or("", |(outer, _)| outer)
+}
+
+pub fn remap(old: &[ClassDef], new: &[ClassDef]) -> Vec<Rename> {
+    let identifiers = identifier_re();
+    let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+
+    // Classes that kept their name pair up directly.
+    let new_by_name: HashMap<String, usize> = new
+        .iter()
+        .enumerate()
+        .filter(|(_, class)| !is_obfuscated(simple_name(class)))
+        .map(|(j, class)| (class.full_name(), j))
+        .collect();
+    for (i, class) in old.iter().enumerate().filter(|(_, c)| !is_obfuscated(simple_name(c))) {
+        if let Some(&j) = new_by_name.get(&class.full_name()) {
+            pairs.push((i, j, 1.0));
+        }
+    }
+
+    // Obfuscated classes are paired twice: the second round can tell types
+    // apart by the class names the first round recovered.
+    let mut class_map = HashMap::new();
+    let mut obfuscated_pairs = Vec::new();
+    for _ in 0..2 {
+        let normalizer = Normalizer::new(&class_map, &identifiers);
+        obfuscated_pairs = pair_obfuscated(old, new, &normalizer);
+        class_map = obfuscated_pairs
+            .iter()
+            .map(|&(i, j, _)| (simple_name(&old[i]).to_string(), simple_name(&new[j]).to_string()))
+            .collect();
+    }
+    pairs.extend(obfuscated_pairs.iter().copied());
+    pairs.sort_by_key(|&(i, _, _)| i);
+
+    let normalizer = Normalizer::new(&class_map, &identifiers);
+    let mut renames = Vec::new();
+    for &(i, j, confidence) in &obfuscated_pairs {
+        let (old_name, new_name) = (simple_name(&old[i]), simple_name(&new[j]));
+        if old_name != new_name {
+            renames.push(Rename {
+                kind: RenameKind::Class,
+                class: new[j].full_name(),
+                old: old_name.to_string(),
+                new: new_name.to_string(),
+                confidence,
+            });
+        }
+    }
+    for &(i, j, confidence) in &pairs {
+        member_renames(&old[i], &new[j], confidence, &normalizer, &mut renames);
+    }
+    renames
+}
+
+// Rewrites obfuscated identifiers in the target set's patterns, regexes and
+// class/member selectors. Renames below `min_confidence` are not used, nor
+// are names that were renamed two different ways; those come back as
+// warnings.
+pub fn apply_renames(
+    set: &mut TargetSet,
+    renames: &[Rename],
+    min_confidence: f32,
+) -> (Vec<TargetChange>, Vec<String>) {
+    let mut map: HashMap<&str, &Rename> = HashMap::new();
+    let mut conflicting = HashSet::new();
+    for rename in renames.iter().filter(|r| r.confidence >= min_confidence) {
+        match map.entry(rename.old.as_str()) {
+            Entry::Vacant(entry) => {
+                entry.insert(rename);
+            }
+            Entry::Occupied(entry) if entry.get().new != rename.new => {
+                conflicting.insert(rename.old.as_str());
+            }
+            Entry::Occupied(_) => {}
+        }
+    }
+    let mut warnings: Vec<String> = conflicting
+        .iter()
+        .map(|old| {
+            let choices: Vec<String> = renames
+                .iter()
+                .filter(|r| r.old == *old && r.confidence >= min_confidence)
+                .map(|r| format!("{} ({})", r.new, r.class))
+                .collect();
+            format!("{} was renamed to {}; left unchanged", old, choices.join(" and "))
+        })
+        .collect();
+    warnings.sort();
+    map.retain(|old, _| !conflicting.contains(old));
+
+    let identifiers = identifier_re();
+    let mut changes = Vec::new();
+    for definition in set.categories.iter_mut().flat_map(|group| group.targets.iter_mut()) {
+        let name = definition.target.name().unwrap_or_default().to_string();
+        for text in target_strings(&mut definition.target) {
+            let after = identifiers
+                .replace_all(text, |caps: &Captures| match map.get(&caps[0]) {
+                    Some(rename) => rename.new.clone(),
+                    None => caps[0].to_string(),
+                })
+                .into_owned();
+            if after != *text {
+                changes.push(TargetChange {
+                    target: name.clone(),
+                    before: std::mem::replace(text, after.clone()),
+                    after,
+                });
+            }
+        }
+    }
+    (changes, warnings)
+}
+
+// The strings of a target that name classes or members.
+fn target_strings(target: &mut Target) -> Vec<&mut String> {
+    match target {
+        Target::Pattern { pattern, .. } | Target::Regex { pattern, .. } => vec![pattern],
+        Target::Field { class, field, .. } | Target::StaticField { class, field, .. } => {
+            let mut strings = vec![class];
+            strings.extend(field.type_name.as_mut());
+            strings.extend(field.name.as_mut());
+            if let Some(FieldAnchor::After(anchor) | FieldAnchor::Before(anchor)) = &mut field.anchor {
+                strings.push(anchor);
+            }
+            strings
+        }
+        Target::Method { class, method, .. } => {
+            let mut strings = vec![class];
+            strings.extend(method.name.as_mut());
+            strings.extend(method.return_type.as_mut());
+            strings.extend(method.params.iter_mut().flatten());
+            strings
+        }
+        Target::TypeIndex { class, .. } => vec![class],
+        _ => Vec::new(),
+    }
+}
+
+// Rewrites type names so the two dumps can be compared: obfuscated names
+// become "?" unless an earlier round paired the class, in which case the
+// old dump's name is swapped for the new one.
+struct Normalizer<'a> {
+    class_map: &'a HashMap<String, String>,
+    known_new: HashSet<&'a str>,
+    identifiers: &'a Regex,
+}
+
+impl<'a> Normalizer<'a> {
+    fn new(class_map: &'a HashMap<String, String>, identifiers: &'a Regex) -> Self {
+        Normalizer {
+            class_map,
+            known_new: class_map.values().map(String::as_str).collect(),
+            identifiers,
+        }
+    }
+
+    fn normalize(&self, text: &str, is_old: bool) -> String {
+        self.identifiers
+            .replace_all(text, |caps: &Captures| {
+                let name = &caps[0];
+                if !is_obfuscated(name) {
+                    name.to_string()
+                } else if is_old {
+                    self.class_map.get(name).cloned().unwrap_or_else(|| "?".to_string())
+                } else if self.known_new.contains(name) {
+                    name.to_string()
+                } else {
+                    "?".to_string()
+                }
+            })
+            .into_owned()
+    }
+
+    fn field_signature(&self, is_static: bool, type_name: &str, is_old: bool) -> String {
+        format!("{}{}", if is_static { "static " } else { "" }, self.normalize(type_name, is_old))
+    }
+
+    fn method_signature(&self, class: &ClassDef, index: usize, is_old: bool) -> String {
+        let method = &class.methods[index];
+        let params: Vec<String> = method
+            .params
+            .iter()
+            .map(|param| self.normalize(&param.type_name, is_old))
+            .collect();
+        format!(
+            "{}{} {}({})",
+            if method.modifiers.iter().any(|m| m == "static") { "static " } else { "" },
+            self.normalize(&method.return_type, is_old),
+            if is_obfuscated(&method.name) { "?" } else { &method.name },
+            params.join(", ")
+        )
+    }
+
+    fn shape(&self, class: &ClassDef, is_old: bool, interner: &mut Interner) -> Shape {
+        let mut methods: Vec<u32> = (0..class.methods.len())
+            .map(|i| interner.id(self.method_signature(class, i, is_old)))
+            .collect();
+        methods.sort_unstable();
+        let mut names: Vec<u32> = class
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .chain(class.properties.iter().map(|p| p.name.as_str()))
+            .chain(class.methods.iter().map(|m| m.name.as_str()))
+            .filter(|name| !self.identifiers.find_iter(name).any(|id| is_obfuscated(id.as_str())))
+            .map(|name| interner.id(name.to_string()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+
+        Shape {
+            kind: class.kind,
+            parent: interner.id(self.normalize(class.parent.as_deref().unwrap_or_default(), is_old)),
+            fields: class
+                .fields
+                .iter()
+                .map(|f| interner.id(self.field_signature(f.is_static(), &f.type_name, is_old)))
+                .collect(),
+            methods,
+            names,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<String, u32>,
+}
+
+impl Interner {
+    fn id(&mut self, text: String) -> u32 {
+        let next = self.ids.len() as u32;
+        *self.ids.entry(text).or_insert(next)
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct Shape {
+    kind: TypeKind,
+    parent: u32,
+    // In declaration order; methods and names sorted.
+    fields: Vec<u32>,
+    methods: Vec<u32>,
+    names: Vec<u32>,
+}
+
+impl Shape {
+    fn similarity(&self, other: &Shape) -> f32 {
+        if self.kind != other.kind {
+            return 0.0;
+        }
+        let mut parts = vec![(0.15, if self.parent == other.parent { 1.0 } else { 0.0 })];
+        if !self.fields.is_empty() || !other.fields.is_empty() {
+            parts.push((0.35, lcs_ratio(&self.fields, &other.fields)));
+        }
+        if !self.methods.is_empty() || !other.methods.is_empty() {
+            parts.push((0.3, sorted_overlap(&self.methods, &other.methods)));
+        }
+        if !self.names.is_empty() || !other.names.is_empty() {
+            parts.push((0.2, sorted_overlap(&self.names, &other.names)));
+        }
+        let weight: f32 = parts.iter().map(|(w, _)| w).sum();
+        parts.iter().map(|(w, score)| w * score).sum::<f32>() / weight
+    }
+
+    // Cheap reject before the full comparison.
+    fn comparable(&self, other: &Shape) -> bool {
+        let close = |a: usize, b: usize| a.min(b) * 2 >= a.max(b);
+        self.kind == other.kind
+            && (close(self.fields.len(), other.fields.len())
+                || close(self.methods.len(), other.methods.len()))
+    }
+}
+
+// Length of the longest common subsequence relative to the average length.
+fn lcs_ratio(a: &[u32], b: &[u32]) -> f32 {
+    let mut previous = vec![0u32; b.len() + 1];
+    let mut current = vec![0u32; b.len() + 1];
+    for x in a {
+        for (j, y) in b.iter().enumerate() {
+            current[j + 1] = if x == y {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    2.0 * previous[b.len()] as f32 / (a.len() + b.len()) as f32
+}
+
+// Dice coefficient of two sorted multisets.
+fn sorted_overlap(a: &[u32], b: &[u32]) -> f32 {
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    2.0 * common as f32 / (a.len() + b.len()) as f32
+}
+
+// The old and new dump's classes that can only pair with each other.
+type Block = (Vec<(usize, Shape)>, Vec<(usize, Shape)>);
+
+// Pairs the obfuscated classes of both dumps within the same namespace and
+// declaring class. Shapes that occur exactly once on each side pair outright;
+// the rest are scored against each other and paired best-first.
+fn pair_obfuscated(old: &[ClassDef], new: &[ClassDef], normalizer: &Normalizer) -> Vec<(usize, usize, f32)> {
+    let mut interner = Interner::default();
+    let mut blocks: HashMap<(String, String), Block> = HashMap::new();
+    for (i, class) in old.iter().enumerate().filter(|(_, c)| is_obfuscated(simple_name(c))) {
+        let key = (class.namespace.clone(), normalizer.normalize(outer_name(class), true));
+        let shape = normalizer.shape(class, true, &mut interner);
+        blocks.entry(key).or_default().0.push((i, shape));
+    }
+    for (j, class) in new.iter().enumerate().filter(|(_, c)| is_obfuscated(simple_name(c))) {
+        let key = (class.namespace.clone(), normalizer.normalize(outer_name(class), false));
+        let shape = normalizer.shape(class, false, &mut interner);
+        if let Some(block) = blocks.get_mut(&key) {
+            block.1.push((j, shape));
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for (old_shapes, new_shapes) in blocks.values() {
+        let mut counts: HashMap<&Shape, (usize, usize)> = HashMap::new();
+        for (_, shape) in old_shapes {
+            counts.entry(shape).or_default().0 += 1;
+        }
+        for (_, shape) in new_shapes {
+            counts.entry(shape).or_default().1 += 1;
+        }
+        let unique = |shape: &Shape| counts.get(shape) == Some(&(1, 1));
+
+        let mut taken_new = HashSet::new();
+        let mut rest_old = Vec::new();
+        for (i, shape) in old_shapes {
+            if unique(shape) {
+                let (j, _) = new_shapes.iter().find(|(_, s)| s == shape).unwrap();
+                pairs.push((*i, *j, 1.0));
+                taken_new.insert(*j);
+            } else {
+                rest_old.push((*i, shape));
+            }
+        }
+        let rest_new: Vec<(usize, &Shape)> = new_shapes
+            .iter()
+            .filter(|(j, _)| !taken_new.contains(j))
+            .map(|(j, shape)| (*j, shape))
+            .collect();
+
+        let mut scored = Vec::new();
+        let mut best_old: HashMap<usize, Vec<f32>> = HashMap::new();
+        let mut best_new: HashMap<usize, Vec<f32>> = HashMap::new();
+        for &(i, old_shape) in &rest_old {
+            for &(j, new_shape) in &rest_new {
+                if !old_shape.comparable(new_shape) {
+                    continue;
+                }
+                let score = old_shape.similarity(new_shape);
+                if score >= MIN_CLASS_SCORE {
+                    scored.push((score, i, j));
+                    best_old.entry(i).or_default().push(score);
+                    best_new.entry(j).or_default().push(score);
+                }
+            }
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        // A pair is a guess when either side had another option nearly as good.
+        let contested = |scores: Option<&Vec<f32>>, score: f32| {
+            scores.is_some_and(|scores| scores.iter().filter(|&&s| s > score - TIE_MARGIN).count() > 1)
+        };
+        let (mut used_old, mut used_new) = (HashSet::new(), HashSet::new());
+        for (score, i, j) in scored {
+            if used_old.contains(&i) || used_new.contains(&j) {
+                continue;
+            }
+            used_old.insert(i);
+            used_new.insert(j);
+            let guess = contested(best_old.get(&i), score) || contested(best_new.get(&j), score);
+            pairs.push((i, j, if guess { score * 0.5 } else { score }));
+        }
+    }
+    pairs
+}
+
+// Fields and methods are grouped by signature; within a group the Nth old
+// member becomes the Nth new one.
+fn member_renames(
+    old: &ClassDef,
+    new: &ClassDef,
+    confidence: f32,
+    normalizer: &Normalizer,
+    renames: &mut Vec<Rename>,
+) {
+    let class = new.full_name();
+    let old_fields: Vec<(String, &str)> = old
+        .fields
+        .iter()
+        .map(|f| (normalizer.field_signature(f.is_static(), &f.type_name, true), f.name.as_str()))
+        .collect();
+    let new_fields: Vec<(String, &str)> = new
+        .fields
+        .iter()
+        .map(|f| (normalizer.field_signature(f.is_static(), &f.type_name, false), f.name.as_str()))
+        .collect();
+    let old_methods: Vec<(String, &str)> = (0..old.methods.len())
+        .map(|i| (normalizer.method_signature(old, i, true), old.methods[i].name.as_str()))
+        .collect();
+    let new_methods: Vec<(String, &str)> = (0..new.methods.len())
+        .map(|i| (normalizer.method_signature(new, i, false), new.methods[i].name.as_str()))
+        .collect();
+
+    for (kind, old_members, new_members) in [
+        (RenameKind::Field, old_fields, new_fields),
+        (RenameKind::Method, old_methods, new_methods),
+    ] {
+        let mut groups: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (signature, name) in &new_members {
+            groups.entry(signature).or_default().push(name);
+        }
+        let mut old_sizes: HashMap<&str, usize> = HashMap::new();
+        for (signature, _) in &old_members {
+            *old_sizes.entry(signature).or_default() += 1;
+        }
+
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        for (signature, old_name) in &old_members {
+            let ordinal = seen.entry(signature).or_default();
+            let position = *ordinal;
+            *ordinal += 1;
+            let Some(group) = groups.get(signature.as_str()) else { continue };
+            let Some(new_name) = group.get(position) else { continue };
+            let factor = if group.len() == old_sizes[signature.as_str()] { 1.0 } else { GROUP_RESIZED_FACTOR };
+
+            // Names like "<KCFEHMAIIINO>k__BackingField" only rename a part.
+            let old_parts = normalizer.identifiers.find_iter(old_name);
+            let new_parts = normalizer.identifiers.find_iter(new_name);
+            for (old_part, new_part) in old_parts.zip(new_parts) {
+                let (old_part, new_part) = (old_part.as_str(), new_part.as_str());
+                if is_obfuscated(old_part) && old_part != new_part {
+                    renames.push(Rename {
+                        kind,
+                        class: class.clone(),
+                        old: old_part.to_string(),
+                        new: new_part.to_string(),
+                        confidence: confidence * factor,
+                    });
+                }
+            }
+        }
+    }
+}
@@ -1,181 +1,894 @@
-use crate::models::{OffsetCategory, OffsetResult, Target};
+use crate::models::{
+    Candidate, ClassDef, FieldAnchor, FieldDef, FieldSelector, Location, Member, MethodComponent,
+    MethodDef, MethodSelector, OffsetCategory, OffsetResult, OffsetValue, Operand, OperandEncoding,
+    Provenance, Target,
+};
+use crate::expr::resolve_expressions;
+use crate::arm64::{self, Instruction};
+use crate::elf::parse_elf;
+use crate::header::parse_header;
+use crate::il2cpp;
+use crate::input::{parse_memory_dump, Input, InputKind};
+use crate::lint::regex_error;
+use crate::metadata::Metadata;
+use crate::parser::for_each_class;
+use crate::pe::parse_pe;
+use crate::script::parse_script;
+use crate::search::{search_all, Needle};
+use crate::signature::Signature;
 use regex::Regex;
-use std::fs;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub fn extract_hex(line: &str) -> Option<String> {
-    let re = Regex::new(r"0x[0-9A-Fa-f]+").ok()?;
-    re.find(line).map(|m| m.as_str().to_string())
+pub fn scan_file(
+    file_path: &str,
+    targets: &[(OffsetCategory, Vec<Target>)],
+) -> Result<Vec<OffsetResult>, String> {
+    let input = Input::read(file_path, None)?;
+    scan_input(file_path, &input, targets)
 }
 
-pub fn scan_file(
+pub fn scan_input(
+    file_path: &str,
+    input: &Input,
+    targets: &[(OffsetCategory, Vec<Target>)],
+) -> Result<Vec<OffsetResult>, String> {
+    match input.kind {
+        InputKind::Gzip | InputKind::Zip => Err(format!(
+            "{} is a {}; extract the dump or library from it first",
+            file_path,
+            input.kind.name()
+        )),
+        InputKind::Metadata => scan_metadata(file_path, input, None, targets),
+        InputKind::ScriptJson => scan_classes(file_path, targets, |handle| {
+            parse_script(&input.bytes)?.into_iter().for_each(handle);
+            Ok(())
+        }),
+        InputKind::Header => scan_classes(file_path, targets, |handle| {
+            parse_header(&mut input.lines()?)?.into_iter().for_each(handle);
+            Ok(())
+        }),
+        kind if kind.is_text() => scan_text(file_path, input, targets),
+        _ => scan_binary(file_path, &BinaryImage::load(input)?, targets),
+    }
+}
+
+fn scan_binary(
     file_path: &str,
+    image: &BinaryImage,
     targets: &[(OffsetCategory, Vec<Target>)],
 ) -> Result<Vec<OffsetResult>, String> {
+    let targets: Vec<(&OffsetCategory, &Target)> = targets
+        .iter()
+        .flat_map(|(category, category_targets)| category_targets.iter().map(move |t| (category, t)))
+        .filter(|(_, target)| target.kind().is_some())
+        .collect();
+
+    // AoB and literal targets become needles that are searched for together.
+    let mut haystacks: Vec<&ByteRegion> = Vec::new();
+    let mut needles = Vec::new();
+    let mut needle_of = Vec::new();
     let mut results = Vec::new();
 
-    // Check if it's likely a binary file
-    let is_binary = file_path.ends_with(".bin") || file_path.ends_with(".so") || file_path.ends_with(".dll");
-
-    if is_binary {
-        let content = fs::read(file_path).map_err(|e| format!("Error reading binary file: {}", e))?;
-        for (category, category_targets) in targets {
-            for target in category_targets {
-                let offset = match target {
-                    Target::AoB { signature, .. } => find_aob_in_bytes(&content, signature),
-                    Target::Pattern { pattern, .. } => find_pattern_in_bytes(&content, pattern),
-                    Target::Regex { pattern, .. } => find_regex_in_bytes(&content, pattern),
-                    Target::Fixed { .. } => None,
-                    Target::Separator => continue,
-                };
-                
-                if let Some(name) = target.name() {
-                    results.push(OffsetResult {
-                        name: name.to_string(),
-                        offset,
-                        category: category.name().to_string(),
-                    });
+    for (category, target) in &targets {
+        let (Some(name), Some(kind)) = (target.name(), target.kind()) else { continue };
+        let mut result = OffsetResult::new(name, category.name(), Provenance::new(file_path, kind));
+
+        let planned = match target {
+            Target::AoB { signature, section, .. } => Signature::parse(signature)
+                .and_then(|signature| Ok((signature, image.code_regions(section.as_deref())?)))
+                .map(|(signature, regions)| Some((signature.value, signature.mask, regions))),
+            Target::Pattern { pattern, .. } if !pattern.is_empty() => Ok(Some((
+                pattern.as_bytes().to_vec(),
+                vec![0xFF; pattern.len()],
+                image.segments.iter().collect(),
+            ))),
+            _ => Ok(None),
+        };
+        needle_of.push(match planned {
+            Ok(Some((value, mask, regions))) => {
+                let indices = regions
+                    .into_iter()
+                    .map(|region| haystack_index(&mut haystacks, region))
+                    .collect();
+                needles.push(Needle { value, mask, haystacks: indices });
+                Some(needles.len() - 1)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                result.error = Some(e);
+                None
+            }
+        });
+        results.push(result);
+    }
+
+    let data: Vec<&[u8]> = haystacks.iter().map(|region| region.data).collect();
+    let hits = search_all(&data, &needles, MAX_LISTED_MATCHES);
+
+    let mut expressions = Vec::new();
+    for (index, (_, target)) in targets.iter().enumerate() {
+        let result = &mut results[index];
+        let hits = needle_of[index].map(|n| &hits[n]);
+        if let Some(hits) = hits {
+            result.elapsed = hits.elapsed;
+        }
+
+        // Matches found, where the scan counts past the candidates it lists.
+        let mut count = None;
+        let candidates = match (target, hits) {
+            (Target::AoB { operand, deref, expect, .. }, Some(hits)) => {
+                count = Some(hits.count);
+                let matches: Vec<_> = hits.listed.iter().map(|&(h, start)| (haystacks[h], start)).collect();
+                let len = needles[needle_of[index].unwrap()].value.len();
+                match resolve_aob_matches(image, &matches, len, operand.as_ref(), deref, hits.count == *expect) {
+                    Ok(candidates) => {
+                        if hits.count != *expect {
+                            result.error =
+                                Some(match_count_error(*expect, hits.count, candidates.len()));
+                        }
+                        candidates
+                    }
+                    Err(e) => {
+                        result.error = Some(e);
+                        Vec::new()
+                    }
                 }
             }
+            (Target::Pattern { pattern, .. }, Some(hits)) => {
+                count = Some(hits.count);
+                hits.listed
+                    .iter()
+                    .map(|&(h, start)| byte_candidate(haystacks[h], start, pattern.len()))
+                    .collect()
+            }
+            (Target::Regex { pattern, .. }, _) => {
+                let started = Instant::now();
+                let mut candidates = Vec::new();
+                match regex::bytes::Regex::new(pattern) {
+                    Ok(re) => {
+                        let mut found = 0;
+                        for region in &image.segments {
+                            for m in re.find_iter(region.data) {
+                                found += 1;
+                                if candidates.len() < MAX_LISTED_MATCHES {
+                                    candidates.push(byte_candidate(region, m.start(), m.len()));
+                                }
+                            }
+                        }
+                        count = Some(found);
+                    }
+                    Err(e) => result.error = Some(format!("invalid regex '{}': {}", pattern, regex_error(&e))),
+                }
+                result.elapsed = started.elapsed();
+                candidates
+            }
+            (Target::Expr { expr, .. }, _) => {
+                defer_expression(result, &mut expressions, index, expr);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+        if result.error.is_some() {
+            result.candidates = candidates;
+        } else {
+            apply_candidates(result, candidates);
+            if let Some(count) = count {
+                result.match_count = count;
+            }
+            // AoB targets declare how many matches they expect, so
+            // differing matches are not ambiguous.
+            if matches!(target, Target::AoB { .. }) {
+                result.ambiguous = false;
+            }
         }
-        return Ok(results);
     }
 
-    let content = fs::read_to_string(file_path)
-        .map_err(|e| format!("Error reading file: {}", e))?;
+    resolve_expressions(&mut results, &expressions);
+    Ok(results)
+}
+
+fn haystack_index<'r, 'a>(haystacks: &mut Vec<&'r ByteRegion<'a>>, region: &'r ByteRegion<'a>) -> usize {
+    match haystacks.iter().position(|known| std::ptr::eq(*known, region)) {
+        Some(index) => index,
+        None => {
+            haystacks.push(region);
+            haystacks.len() - 1
+        }
+    }
+}
 
-    let lines: Vec<&str> = content.lines().collect();
+fn scan_text(
+    file_path: &str,
+    input: &Input,
+    targets: &[(OffsetCategory, Vec<Target>)],
+) -> Result<Vec<OffsetResult>, String> {
+    // Classes are matched as the parser completes them and then dropped, so
+    // memory stays bounded by the largest class rather than the dump.
+    scan_classes(file_path, targets, |handle| for_each_class(&mut input.lines()?, handle))
+}
+
+// Scans a global-metadata.dat together with the libil2cpp binary it belongs
+// to, which is looked for next to it unless `binary` names one.
+pub fn scan_metadata(
+    file_path: &str,
+    input: &Input,
+    binary: Option<&str>,
+    targets: &[(OffsetCategory, Vec<Target>)],
+) -> Result<Vec<OffsetResult>, String> {
+    let binary = match binary {
+        Some(path) => PathBuf::from(path),
+        None => il2cpp::find_binary(Path::new(file_path)).ok_or_else(|| {
+            format!(
+                "no libil2cpp.so or GameAssembly.dll found near {}; pass it with --binary",
+                file_path
+            )
+        })?,
+    };
+    let binary_input = Input::read(&binary.to_string_lossy(), None)?;
+    let image = BinaryImage::load(&binary_input)?;
+    let metadata = Metadata::parse(&input.bytes)?;
+    let model = il2cpp::build_model(&metadata, &image)
+        .map_err(|e| format!("{}: {}", binary.display(), e))?;
+
+    scan_classes(file_path, targets, |handle| {
+        model.classes.into_iter().for_each(handle);
+        Ok(())
+    })
+}
+
+// Matches class targets against the classes `classes` passes to its handler.
+fn scan_classes(
+    file_path: &str,
+    targets: &[(OffsetCategory, Vec<Target>)],
+    classes: impl FnOnce(&mut dyn FnMut(ClassDef)) -> Result<(), String>,
+) -> Result<Vec<OffsetResult>, String> {
+    let mut results = Vec::new();
+    let mut expressions = Vec::new();
+    let mut matchers = Vec::new();
 
     for (category, category_targets) in targets {
         for target in category_targets {
-            let result = match target {
-                Target::Fixed { name, hex } => OffsetResult {
-                    name: name.clone(),
-                    offset: Some(hex.clone()),
-                    category: category.name().to_string(),
+            let (Some(name), Some(kind)) = (target.name(), target.kind()) else { continue };
+            let mut result =
+                OffsetResult::new(name, category.name(), Provenance::new(file_path, kind));
+
+            match target {
+                Target::Fixed { hex, .. } => match OffsetValue::parse(hex) {
+                    Ok(value) => result.offset = Some(value),
+                    Err(e) => result.error = Some(e),
                 },
-                Target::Pattern { name, pattern } => {
-                    let offset = find_pattern_in_lines(&lines, pattern);
-                    OffsetResult {
-                        name: name.clone(),
-                        offset,
-                        category: category.name().to_string(),
-                    }
-                }
-                Target::Regex { name, pattern } => {
-                    let offset = find_regex_in_lines(&lines, pattern);
-                    OffsetResult {
-                        name: name.clone(),
-                        offset,
-                        category: category.name().to_string(),
-                    }
+                Target::Expr { expr, .. } => {
+                    defer_expression(&mut result, &mut expressions, results.len(), expr);
                 }
-                Target::AoB { name, .. } => OffsetResult {
-                    name: name.clone(),
-                    offset: None,
-                    category: category.name().to_string(),
-                },
-                Target::Separator => continue,
-            };
+                _ => {}
+            }
+            matchers.push(ClassMatcher::new(target).map(|matcher| (matcher, Vec::new(), 0, None)));
             results.push(result);
         }
     }
 
+    classes(&mut |class| {
+        for (result, (matcher, candidates, count, miss)) in results
+            .iter_mut()
+            .zip(&mut matchers)
+            .filter_map(|(result, matcher)| Some((result, matcher.as_mut()?)))
+        {
+            let started = Instant::now();
+            let listed = candidates.len();
+            if let Some(reason) = matcher.find(&class, candidates) {
+                miss.get_or_insert(reason);
+            }
+            // Every match is counted, but only the first few are kept.
+            *count += candidates.len() - listed;
+            candidates.truncate(MAX_LISTED_MATCHES);
+            result.elapsed += started.elapsed();
+        }
+    })?;
+
+    for (result, matcher) in results.iter_mut().zip(matchers) {
+        if let Some((_, candidates, count, miss)) = matcher {
+            apply_candidates(result, candidates);
+            result.match_count = count;
+            // Why a member that was found gave no value, when nothing else did.
+            if result.offset.is_none() {
+                result.error = result.error.take().or(miss);
+            }
+        }
+    }
+
+    resolve_expressions(&mut results, &expressions);
     Ok(results)
 }
 
-fn find_pattern_in_lines(lines: &[&str], pattern: &str) -> Option<String> {
-    for line in lines {
-        if line.contains(pattern) {
-            if let Some(offset) = extract_hex(line) {
-                return Some(offset);
+// A contiguous run of input bytes and the address its first byte maps to.
+// Plain binaries are one region at address 0, so offsets stay file offsets.
+#[derive(Clone)]
+pub struct ByteRegion<'a> {
+    pub name: Option<String>,
+    pub data: &'a [u8],
+    pub file_offset: u64,
+    pub address: u64,
+    pub executable: bool,
+}
+
+impl<'a> ByteRegion<'a> {
+    fn new(bytes: &'a [u8], file_offset: u64, size: u64, address: u64) -> Option<Self> {
+        let start = usize::try_from(file_offset).ok()?;
+        let end = start.checked_add(usize::try_from(size).ok()?)?;
+        Some(ByteRegion {
+            name: None,
+            data: bytes.get(start..end)?,
+            file_offset,
+            address,
+            executable: true,
+        })
+    }
+}
+
+// What a binary input looks like once loaded: the segments that are mapped
+// at runtime, plus named sections where the format has them. Pointers stored
+// in the input are `pointer_size` bytes wide and relative to `image_base`;
+// `relocations` holds the ones the loader writes rather than the file.
+pub struct BinaryImage<'a> {
+    pub segments: Vec<ByteRegion<'a>>,
+    pub sections: Vec<ByteRegion<'a>>,
+    pub pointer_size: usize,
+    pub image_base: u64,
+    relocations: HashMap<u64, u64>,
+}
+
+impl<'a> BinaryImage<'a> {
+    pub fn load(input: &'a Input) -> Result<Self, String> {
+        let bytes = &input.bytes;
+        let mut image = BinaryImage {
+            segments: Vec::new(),
+            sections: Vec::new(),
+            pointer_size: 8,
+            image_base: 0,
+            relocations: HashMap::new(),
+        };
+
+        match input.kind {
+            InputKind::MemoryDump => {
+                image.segments = parse_memory_dump(bytes)?
+                    .into_iter()
+                    // Every region stays searchable: the flags are the host's
+                    // page protections, and the emulator keeps guest code in
+                    // memory the host sees as read/write.
+                    .filter_map(|region| {
+                        ByteRegion::new(bytes, region.file_offset as u64, region.size as u64, region.base)
+                    })
+                    .collect();
+            }
+            InputKind::Elf => {
+                let elf = parse_elf(bytes)?;
+                image.pointer_size = if elf.is_64 { 8 } else { 4 };
+                image.relocations = elf.relocations.iter().copied().collect();
+                image.segments = elf
+                    .segments
+                    .iter()
+                    .filter(|segment| segment.is_load())
+                    .filter_map(|segment| {
+                        let mut region =
+                            ByteRegion::new(bytes, segment.offset, segment.file_size, segment.vaddr)?;
+                        region.executable = segment.is_executable();
+                        Some(region)
+                    })
+                    .collect();
+                image.sections = elf
+                    .sections
+                    .iter()
+                    .filter(|section| section.is_loaded())
+                    .filter_map(|section| {
+                        let mut region =
+                            ByteRegion::new(bytes, section.offset, section.size, section.addr)?;
+                        region.name = Some(section.name.clone());
+                        region.executable = section.is_executable();
+                        Some(region)
+                    })
+                    .collect();
+            }
+            InputKind::Pe => {
+                let pe = parse_pe(bytes)?;
+                image.pointer_size = if pe.is_64 { 8 } else { 4 };
+                image.image_base = pe.image_base;
+                image.sections = pe
+                    .sections
+                    .iter()
+                    .filter_map(|section| {
+                        let mut region =
+                            ByteRegion::new(bytes, section.file_offset, section.file_size, section.rva)?;
+                        region.name = Some(section.name.clone());
+                        region.executable = section.is_executable();
+                        Some(region)
+                    })
+                    .collect();
+                // PE sections are what gets mapped, so they double as segments.
+                image.segments = image.sections.clone();
+            }
+            _ => {}
+        }
+
+        if image.segments.is_empty() && input.kind != InputKind::MemoryDump {
+            image.segments = ByteRegion::new(bytes, 0, bytes.len() as u64, 0).into_iter().collect();
+            image.sections.clear();
+        }
+        Ok(image)
+    }
+
+    // Where code signatures are searched: the named section if one is given,
+    // otherwise every executable segment (every region of a memory dump).
+    fn code_regions(&self, section: Option<&str>) -> Result<Vec<&ByteRegion<'a>>, String> {
+        let Some(section) = section else {
+            return Ok(self.segments.iter().filter(|s| s.executable).collect());
+        };
+        let regions: Vec<_> = self
+            .sections
+            .iter()
+            .filter(|s| s.name.as_deref() == Some(section))
+            .collect();
+        if regions.is_empty() {
+            return Err(format!("input has no section '{}'", section));
+        }
+        Ok(regions)
+    }
+
+    pub fn read(&self, address: u64, size: usize) -> Option<&'a [u8]> {
+        self.segments.iter().find_map(|segment| {
+            let start = usize::try_from(address.checked_sub(segment.address)?).ok()?;
+            segment.data.get(start..start.checked_add(size)?)
+        })
+    }
+
+    // Unsigned little-endian integer of `size` bytes at `address`.
+    pub fn read_value(&self, address: u64, size: usize) -> Option<u64> {
+        self.read(address, size).map(read_le)
+    }
+
+    pub fn file_offset(&self, address: u64) -> Option<u64> {
+        self.segments.iter().find_map(|segment| {
+            let start = address.checked_sub(segment.address)?;
+            (start < segment.data.len() as u64).then_some(segment.file_offset + start)
+        })
+    }
+
+    // Reads the pointer stored at `address` and returns what it points to.
+    pub fn read_pointer(&self, address: u64) -> Result<u64, String> {
+        let bytes = self
+            .read(address, self.pointer_size)
+            .ok_or_else(|| format!("address 0x{:X} is not mapped in the input", address))?;
+        let pointer = match self.relocations.get(&address) {
+            Some(&value) => value,
+            None => read_le(bytes),
+        };
+        pointer.checked_sub(self.image_base).ok_or_else(|| {
+            format!("value 0x{:X} at 0x{:X} does not point into the image", pointer, address)
+        })
+    }
+
+    // Follows an AoB match to the address its target is after.
+    fn resolve(
+        &self,
+        region: &ByteRegion,
+        start: usize,
+        operand: Option<&Operand>,
+        deref: &[u64],
+    ) -> Result<u64, String> {
+        let match_address = region.address + start as u64;
+        let mut address = match operand {
+            Some(operand) if operand.encoding != OperandEncoding::Value => {
+                resolve_arm64(region, start + operand.offset, operand.encoding)?
+            }
+            Some(operand) => {
+                if !matches!(operand.size, 1 | 2 | 4 | 8) {
+                    return Err(format!("operand size {} is not 1, 2, 4 or 8", operand.size));
+                }
+                let at = start + operand.offset;
+                let bytes = region
+                    .data
+                    .get(at..at + operand.size)
+                    .ok_or_else(|| "operand runs past the end of the input".to_string())?;
+                let value = read_le(bytes);
+                match operand.instruction_length {
+                    Some(length) => {
+                        let shift = 64 - 8 * operand.size as u32;
+                        let displacement = ((value << shift) as i64) >> shift;
+                        let next = match_address + length as u64;
+                        next.checked_add_signed(displacement).ok_or_else(|| {
+                            format!("displacement {} from 0x{:X} is out of range", displacement, next)
+                        })?
+                    }
+                    None => value,
+                }
             }
+            None => match_address,
+        };
+
+        for (step, offset) in deref.iter().enumerate() {
+            address = self
+                .read_pointer(address.wrapping_add(*offset))
+                .map_err(|e| format!("deref step {}: {}", step + 1, e))?;
         }
+        Ok(address)
     }
-    None
 }
 
-fn find_regex_in_lines(lines: &[&str], pattern: &str) -> Option<String> {
-    let re = Regex::new(pattern).ok()?;
-    for line in lines {
-        if re.is_match(line) {
-            if let Some(offset) = extract_hex(line) {
-                return Some(offset);
+// How far past an ADRP to look for the ADD/LDR that uses its register;
+// compilers often schedule other instructions in between.
+const ADRP_PAIR_WINDOW: usize = 8;
+
+fn resolve_arm64(region: &ByteRegion, at: usize, encoding: OperandEncoding) -> Result<u64, String> {
+    let word_at = |position: usize| {
+        region
+            .data
+            .get(position..position + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    let pc = region.address.wrapping_add(at as u64);
+    let word = word_at(at).ok_or_else(|| "operand runs past the end of the input".to_string())?;
+
+    match (encoding, arm64::decode(word)) {
+        (OperandEncoding::Branch, Instruction::Branch { offset }) => Ok(pc.wrapping_add_signed(offset)),
+        (OperandEncoding::Adrp, Instruction::Adr { offset, .. }) => Ok(pc.wrapping_add_signed(offset)),
+        (OperandEncoding::Adrp, Instruction::Adrp { rd, offset }) => {
+            let page = (pc & !0xFFF).wrapping_add_signed(offset);
+            for i in 1..=ADRP_PAIR_WINDOW {
+                let Some(next) = word_at(at + 4 * i) else { break };
+                match arm64::decode(next) {
+                    Instruction::AddImmediate { rn, offset, .. } | Instruction::Load { rn, offset, .. }
+                        if rn == rd =>
+                    {
+                        return Ok(page.wrapping_add(offset));
+                    }
+                    Instruction::Adrp { rd: other, .. } if other == rd => break,
+                    _ => {}
+                }
             }
+            Ok(page)
         }
+        (encoding, _) => Err(format!(
+            "expected {} at 0x{:X}, found instruction 0x{:08X}",
+            if encoding == OperandEncoding::Branch { "B/BL" } else { "ADRP/ADR" },
+            pc,
+            word
+        )),
     }
-    None
 }
 
-fn find_aob_in_bytes(bytes: &[u8], signature: &str) -> Option<String> {
-    let tokens: Vec<&str> = signature.split_whitespace().collect();
-    let mut pattern = Vec::new();
-    let mut mask = Vec::new();
+fn read_le(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0, |value, &byte| (value << 8) | byte as u64)
+}
+
+fn defer_expression(
+    result: &mut OffsetResult,
+    expressions: &mut Vec<(usize, String)>,
+    index: usize,
+    expr: &str,
+) {
+    result.provenance.text = Some(expr.to_string());
+    expressions.push((index, expr.to_string()));
+}
+
+// The first candidate wins; every candidate is kept so conflicting hits show
+// up instead of silently picking one.
+fn apply_candidates(result: &mut OffsetResult, candidates: Vec<Candidate>) {
+    result.offset = candidates.first().map(|c| c.offset.clone());
+    result.ambiguous = candidates
+        .iter()
+        .any(|c| Some(&c.offset) != result.offset.as_ref());
+
+    if let Some(first) = candidates.first() {
+        result.provenance.location = Some(first.location);
+        result.provenance.class = first.class.clone();
+        result.provenance.text = Some(first.text.clone());
+    }
+    result.match_count = candidates.len();
+    result.candidates = candidates;
+}
+
+// What a text target looks for in each class of the dump.
+enum ClassMatcher {
+    Pattern(String),
+    Regex(Regex),
+    Field(Regex, FieldMatcher),
+    Method(Regex, MethodMatcher),
+    TypeIndex(Regex),
+}
 
-    for token in tokens {
-        if token == "?" || token == "??" {
-            pattern.push(0u8);
-            mask.push(false);
-        } else if let Ok(byte) = u8::from_str_radix(token, 16) {
-            pattern.push(byte);
-            mask.push(true);
+impl ClassMatcher {
+    fn new(target: &Target) -> Option<Self> {
+        match target {
+            Target::Pattern { pattern, .. } => Some(ClassMatcher::Pattern(pattern.clone())),
+            Target::Regex { pattern, .. } => Regex::new(pattern).ok().map(ClassMatcher::Regex),
+            Target::Field { class, field, .. } => Some(ClassMatcher::Field(
+                full_match_regex(class)?,
+                FieldMatcher::new(field, Some(false))?,
+            )),
+            Target::StaticField { class, field, .. } => Some(ClassMatcher::Field(
+                full_match_regex(class)?,
+                FieldMatcher::new(field, Some(true))?,
+            )),
+            Target::Method { class, method, .. } => Some(ClassMatcher::Method(
+                full_match_regex(class)?,
+                MethodMatcher::new(method)?,
+            )),
+            Target::TypeIndex { class, .. } => full_match_regex(class).map(ClassMatcher::TypeIndex),
+            _ => None,
         }
     }
 
-    if pattern.is_empty() {
-        return None;
+    // Adds the class's matches to `candidates`. A member that matched but has
+    // no value to report comes back as the reason instead.
+    fn find(&self, class: &ClassDef, candidates: &mut Vec<Candidate>) -> Option<String> {
+        let members: Vec<Member> = match self {
+            ClassMatcher::Pattern(pattern) => {
+                class.members().filter(|m| m.text().contains(pattern.as_str())).collect()
+            }
+            ClassMatcher::Regex(re) => class.members().filter(|m| re.is_match(m.text())).collect(),
+            ClassMatcher::Field(class_re, matcher) => {
+                if !class_matches(class_re, class) {
+                    return None;
+                }
+                matcher.select(class).map(Member::Field).into_iter().collect()
+            }
+            ClassMatcher::Method(class_re, matcher) => {
+                if !class_matches(class_re, class) {
+                    return None;
+                }
+                let method = matcher.select(class)?;
+                let Some(value) = matcher.component.value(method) else {
+                    let component = matcher.component.name();
+                    return Some(format!("{}.{} has no {}", class.full_name(), method.name, component));
+                };
+                candidates.push(member_candidate(class, &Member::Method(method), value));
+                return None;
+            }
+            ClassMatcher::TypeIndex(class_re) => {
+                if let Some(index) = class.type_def_index.filter(|_| class_matches(class_re, class)) {
+                    candidates.push(Candidate {
+                        location: class.location,
+                        class: Some(class.full_name()),
+                        text: format!("{} // TypeDefIndex: {}", class.full_name(), index),
+                        // Shown in decimal, as dump.cs writes it.
+                        offset: OffsetValue {
+                            value: index as u64,
+                            text: index.to_string(),
+                        },
+                    });
+                }
+                return None;
+            }
+        };
+        candidates.extend(
+            members
+                .iter()
+                .filter_map(|member| Some(member_candidate(class, member, member.value()?))),
+        );
+        None
     }
+}
 
-    for i in 0..=(bytes.len().saturating_sub(pattern.len())) {
-        let mut found = true;
-        for (j, (&p, &m)) in pattern.iter().zip(mask.iter()).enumerate() {
-            if m && bytes[i + j] != p {
-                found = false;
-                break;
+fn class_matches(class_re: &Regex, class: &ClassDef) -> bool {
+    class_re.is_match(&class.name) || class_re.is_match(&class.full_name())
+}
+
+fn member_candidate(class: &ClassDef, member: &Member, value: u64) -> Candidate {
+    Candidate {
+        location: member.location(),
+        class: Some(class.full_name()),
+        text: member.text().trim().to_string(),
+        offset: OffsetValue::new(value),
+    }
+}
+
+struct FieldMatcher {
+    modifiers: Vec<String>,
+    type_re: Option<Regex>,
+    name_re: Option<Regex>,
+    anchor: Option<(bool, Regex)>,
+    index: usize,
+    // Some(true) for static fields only, Some(false) for instance fields
+    // only, None for either.
+    is_static: Option<bool>,
+}
+
+impl FieldMatcher {
+    fn new(selector: &FieldSelector, is_static: Option<bool>) -> Option<Self> {
+        let compile = |pattern: &Option<String>| match pattern {
+            Some(pattern) => full_match_regex(pattern).map(Some),
+            None => Some(None),
+        };
+        let anchor = match &selector.anchor {
+            Some(FieldAnchor::After(pattern)) => Some((true, full_match_regex(pattern)?)),
+            Some(FieldAnchor::Before(pattern)) => Some((false, full_match_regex(pattern)?)),
+            None => None,
+        };
+        Some(FieldMatcher {
+            modifiers: selector.modifiers.clone(),
+            type_re: compile(&selector.type_name)?,
+            name_re: compile(&selector.name)?,
+            anchor,
+            index: selector.index,
+            is_static,
+        })
+    }
+
+    fn matches(&self, field: &FieldDef) -> bool {
+        field.offset.is_some()
+            && self.is_static.is_none_or(|is_static| field.is_static() == is_static)
+            && self.modifiers.iter().all(|modifier| field.modifiers.contains(modifier))
+            && self.type_re.as_ref().is_none_or(|re| re.is_match(&field.type_name))
+            && self.name_re.as_ref().is_none_or(|re| re.is_match(&field.name))
+    }
+
+    fn select<'a>(&self, class: &'a ClassDef) -> Option<&'a FieldDef> {
+        let fields = &class.fields;
+        let candidates: Box<dyn Iterator<Item = &FieldDef>> = match &self.anchor {
+            Some((after, anchor_re)) => {
+                let position = fields.iter().position(|f| anchor_re.is_match(&f.name))?;
+                if *after {
+                    Box::new(fields[position + 1..].iter())
+                } else {
+                    Box::new(fields[..position].iter().rev())
+                }
             }
-        }
-        if found {
-            return Some(format!("0x{:X}", i));
-        }
+            None => Box::new(fields.iter()),
+        };
+
+        candidates.filter(|field| self.matches(field)).nth(self.index)
+    }
+}
+
+struct MethodMatcher {
+    name_re: Option<Regex>,
+    return_re: Option<Regex>,
+    param_res: Option<Vec<Regex>>,
+    index: usize,
+    component: MethodComponent,
+}
+
+impl MethodMatcher {
+    fn new(selector: &MethodSelector) -> Option<Self> {
+        let compile = |pattern: &Option<String>| match pattern {
+            Some(pattern) => full_match_regex(pattern).map(Some),
+            None => Some(None),
+        };
+        let param_res = match &selector.params {
+            Some(params) => Some(params.iter().map(|p| full_match_regex(p)).collect::<Option<_>>()?),
+            None => None,
+        };
+        Some(MethodMatcher {
+            name_re: compile(&selector.name)?,
+            return_re: compile(&selector.return_type)?,
+            param_res,
+            index: selector.index,
+            component: selector.component,
+        })
+    }
+
+    fn matches(&self, method: &MethodDef) -> bool {
+        self.name_re.as_ref().is_none_or(|re| re.is_match(&method.name))
+            && self.return_re.as_ref().is_none_or(|re| re.is_match(&method.return_type))
+            && self.param_res.as_ref().is_none_or(|res| {
+                res.len() == method.params.len()
+                    && res.iter().zip(&method.params).all(|(re, p)| re.is_match(&p.type_name))
+            })
     }
 
-    None
+    fn select<'a>(&self, class: &'a ClassDef) -> Option<&'a MethodDef> {
+        class.methods.iter().filter(|method| self.matches(method)).nth(self.index)
+    }
+}
+
+fn full_match_regex(pattern: &str) -> Option<Regex> {
+    Regex::new(&format!("^(?:{})$", pattern)).ok()
 }
 
-fn find_pattern_in_bytes(bytes: &[u8], pattern: &str) -> Option<String> {
-    let p_bytes = pattern.as_bytes();
-    if p_bytes.is_empty() { return None; }
-    
-    for i in 0..=(bytes.len().saturating_sub(p_bytes.len())) {
-        if &bytes[i..i+p_bytes.len()] == p_bytes {
-            return Some(format!("0x{:X}", i));
+fn byte_candidate(region: &ByteRegion, start: usize, len: usize) -> Candidate {
+    let bytes = region.data;
+    let matched = &bytes[start..(start + len.min(32)).min(bytes.len())];
+    Candidate {
+        location: Location::Byte(region.file_offset + start as u64),
+        class: None,
+        text: matched
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" "),
+        offset: OffsetValue::new(region.address + start as u64),
+    }
+}
+
+// Matches past this many are counted but not listed.
+const MAX_LISTED_MATCHES: usize = 32;
+
+// Turns listed AoB matches into candidates, resolved through
+// `operand`/`deref`. Resolution errors fail the target only when the match
+// count is right; otherwise the unresolved match address is listed.
+fn resolve_aob_matches(
+    image: &BinaryImage,
+    matches: &[(&ByteRegion, usize)],
+    len: usize,
+    operand: Option<&Operand>,
+    deref: &[u64],
+    count_ok: bool,
+) -> Result<Vec<Candidate>, String> {
+    let mut candidates = Vec::new();
+    for &(region, start) in matches {
+        let mut candidate = byte_candidate(region, start, len);
+        if operand.is_some() || !deref.is_empty() {
+            match image.resolve(region, start, operand, deref) {
+                Ok(address) => candidate.offset = OffsetValue::new(address),
+                Err(e) if count_ok => return Err(e),
+                Err(_) => {}
+            }
         }
+        candidates.push(candidate);
     }
-    None
+    Ok(candidates)
 }
 
-fn find_regex_in_bytes(bytes: &[u8], pattern: &str) -> Option<String> {
-    // We use the bytes version of regex for binary data
-    let re = regex::bytes::Regex::new(pattern).ok()?;
-    re.find(bytes).map(|m| format!("0x{:X}", m.start()))
+fn match_count_error(expect: usize, count: usize, listed: usize) -> String {
+    let plural = |n: usize| if n == 1 { "match" } else { "matches" };
+    let mut message = format!("expected {} {}, found {}", expect, plural(expect), count);
+    if listed < count {
+        message.push_str(&format!(" (first {} listed)", listed));
+    }
+    message
 }
 
-pub fn batch_scan(
+pub struct BatchEntry<T> {
+    pub path: String,
+    pub result: Result<T, String>,
+    pub elapsed: Duration,
+}
+
+// Runs `scan` over every file on a pool of `threads` workers. Files are
+// handed out in order as workers free up; entries come back in input order
+// whatever order they finish in.
+pub fn batch_scan<T: Send>(
     file_paths: &[String],
-    targets: &[(OffsetCategory, Vec<Target>)],
-) -> Vec<(String, Result<Vec<OffsetResult>, String>)> {
-    file_paths
-        .iter()
-        .map(|path| {
-            let result = scan_file(path, targets);
-            (path.clone(), result)
-        })
+    threads: usize,
+    scan: impl Fn(&str) -> Result<T, String> + Sync,
+) -> Vec<BatchEntry<T>> {
+    let next = AtomicUsize::new(0);
+    let slots: Vec<Mutex<Option<BatchEntry<T>>>> = file_paths.iter().map(|_| Mutex::new(None)).collect();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, file_paths.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = file_paths.get(index) else { break };
+                let started = Instant::now();
+                let result = scan(path);
+                *slots[index].lock().unwrap() = Some(BatchEntry {
+                    path: path.clone(),
+                    result,
+                    elapsed: started.elapsed(),
+                });
+            });
+        }
+    });
+
+    slots
+        .into_iter()
+        .filter_map(|slot| slot.into_inner().unwrap())
         .collect()
 }
 
+#[allow(dead_code)]
 pub fn compare_offsets(
     old_results: &[OffsetResult],
     new_results: &[OffsetResult],
-) -> Vec<(String, Option<String>, Option<String>)> {
+) -> Vec<(String, Option<OffsetValue>, Option<OffsetValue>)> {
     let mut comparisons = Vec::new();
 
     for new_result in new_results {
@@ -0,0 +1,178 @@
+// Reader for Il2CppDumper's script.json. Only the ScriptMethod list is used:
+// each entry's address, "Namespace.Class$$Method" name and C signature
+// become a method of that class, so pattern and regex targets see them the
+// way they see dump.cs method lines.
+
+use crate::models::{ClassDef, Location, MethodDef, ParamDef, TypeKind};
+use memchr::memmem;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct ScriptMethod {
+    #[serde(rename = "Address")]
+    address: u64,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Signature", default)]
+    signature: String,
+}
+
+// Objects are cut out of the ScriptMethod array one at a time instead of
+// deserializing the whole file, which keeps memory flat on large games and
+// gives each method the line it starts on.
+pub fn parse_script(bytes: &[u8]) -> Result<Vec<ClassDef>, String> {
+    let Some(key) = memmem::find(bytes, b"\"ScriptMethod\"") else {
+        return Err("script.json has no ScriptMethod list".to_string());
+    };
+    let mut at = key + b"\"ScriptMethod\"".len();
+    at = skip_until(bytes, at, b'[').ok_or("script.json: ScriptMethod is not a list")? + 1;
+
+    let mut classes: Vec<ClassDef> = Vec::new();
+    let mut class_index: HashMap<String, usize> = HashMap::new();
+    let mut line = 1 + memchr::memchr_iter(b'\n', &bytes[..at]).count();
+
+    loop {
+        let start = bytes[at..]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace() && *b != b',')
+            .map(|skip| at + skip)
+            .ok_or("script.json ends inside ScriptMethod")?;
+        line += memchr::memchr_iter(b'\n', &bytes[at..start]).count();
+        match bytes[start] {
+            b']' => break,
+            b'{' => {}
+            other => {
+                return Err(format!(
+                    "script.json line {}: expected a method object, found '{}'",
+                    line, other as char
+                ))
+            }
+        }
+
+        let end = object_end(bytes, start)
+            .ok_or_else(|| format!("script.json line {}: unterminated method object", line))?;
+        let entry: ScriptMethod = serde_json::from_slice(&bytes[start..end])
+            .map_err(|e| format!("script.json line {}: {}", line, e))?;
+
+        let (class_name, method) = method_from_entry(entry, line);
+        let index = *class_index.entry(class_name.clone()).or_insert_with(|| {
+            classes.push(class_from_name(&class_name, line));
+            classes.len() - 1
+        });
+        classes[index].methods.push(method);
+
+        line += memchr::memchr_iter(b'\n', &bytes[start..end]).count();
+        at = end;
+    }
+
+    Ok(classes)
+}
+
+fn skip_until(bytes: &[u8], from: usize, wanted: u8) -> Option<usize> {
+    let skip = bytes.get(from..)?.iter().position(|b| !b.is_ascii_whitespace() && *b != b':')?;
+    (bytes[from + skip] == wanted).then_some(from + skip)
+}
+
+// One past the '}' closing the object at `start`, skipping braces in strings.
+fn object_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    for (i, &byte) in bytes.iter().enumerate().skip(start) {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn method_from_entry(entry: ScriptMethod, line: usize) -> (String, MethodDef) {
+    let (class_name, name) = match entry.name.rsplit_once("$$") {
+        Some((class_name, name)) => (class_name.to_string(), name.to_string()),
+        None => (String::new(), entry.name.clone()),
+    };
+
+    // "void Game_Player__Update (Game_Player_o* __this, const MethodInfo* method);"
+    let (head, args) = entry.signature.split_once('(').unwrap_or((&entry.signature, ""));
+    let return_type = head.trim().rsplit_once(' ').map_or("", |(ret, _)| ret.trim()).to_string();
+    let all_params: Vec<ParamDef> = args
+        .trim_end_matches(';')
+        .trim_end_matches(')')
+        .split(',')
+        .filter_map(|param| {
+            let (type_name, name) = param.trim().rsplit_once(' ')?;
+            Some(ParamDef {
+                type_name: type_name.trim().to_string(),
+                name: name.to_string(),
+            })
+        })
+        .collect();
+    let is_static = all_params.first().is_none_or(|param| param.name != "__this");
+    // The instance and MethodInfo arguments are IL2CPP's, not the method's.
+    let params = all_params
+        .into_iter()
+        .filter(|param| param.name != "__this" && !param.type_name.ends_with("MethodInfo*"))
+        .collect();
+
+    let method = MethodDef {
+        modifiers: if is_static { vec!["static".to_string()] } else { Vec::new() },
+        return_type,
+        name,
+        params,
+        rva: Some(entry.address),
+        offset: None,
+        va: None,
+        slot: None,
+        location: Location::Line(line),
+        text: format!("{} // {}", entry.signature, entry.name),
+    };
+    (class_name, method)
+}
+
+// Splits "Game.Outer.Inner" at its last dot outside generic arguments.
+fn class_from_name(full_name: &str, line: usize) -> ClassDef {
+    let mut depth = 0i32;
+    let mut split = None;
+    for (i, c) in full_name.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            '.' if depth == 0 => split = Some(i),
+            _ => {}
+        }
+    }
+    let (namespace, name) = match split {
+        Some(i) => (&full_name[..i], &full_name[i + 1..]),
+        None => ("", full_name),
+    };
+
+    ClassDef {
+        namespace: namespace.to_string(),
+        name: name.to_string(),
+        kind: TypeKind::Class,
+        modifiers: Vec::new(),
+        parent: None,
+        interfaces: Vec::new(),
+        type_def_index: None,
+        location: Location::Line(line),
+        fields: Vec::new(),
+        properties: Vec::new(),
+        methods: Vec::new(),
+    }
+}
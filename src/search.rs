@@ -0,0 +1,118 @@
+use memchr::memchr_iter;
+use std::time::{Duration, Instant};
+
+// Bytes are searched chunk by chunk so every needle runs over a chunk while
+// it is still in cache. Each needle still makes its own memchr pass over the
+// chunk, so the work grows with the number of targets.
+const CHUNK_SIZE: usize = 1 << 20;
+const SAMPLE_STRIDE: usize = 61;
+
+// A byte pattern to find: `byte & mask == value` at every position. Literal
+// strings are needles with a full mask.
+pub struct Needle {
+    pub value: Vec<u8>,
+    pub mask: Vec<u8>,
+    // Indices into the haystack list this needle is searched in.
+    pub haystacks: Vec<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Hits {
+    pub count: usize,
+    // (haystack index, start), in input order, capped at `max_listed`.
+    pub listed: Vec<(usize, usize)>,
+    pub elapsed: Duration,
+}
+
+impl Needle {
+    fn matches_at(&self, data: &[u8], start: usize) -> bool {
+        data.get(start..start + self.value.len()).is_some_and(|window| {
+            window
+                .iter()
+                .zip(self.value.iter().zip(&self.mask))
+                .all(|(&byte, (&value, &mask))| byte & mask == value)
+        })
+    }
+}
+
+// Finds every needle in its haystacks. Each needle is prefiltered on its
+// rarest fully-fixed byte with memchr, run per needle over the shared chunks,
+// and only verified where that byte occurs. Rarity comes from a sampled
+// histogram taken in a separate pass first.
+pub fn search_all(haystacks: &[&[u8]], needles: &[Needle], max_listed: usize) -> Vec<Hits> {
+    let frequency = byte_frequency(haystacks);
+    let anchors: Vec<Option<usize>> = needles
+        .iter()
+        .map(|needle| choose_anchor(needle, &frequency))
+        .collect();
+    let mut hits: Vec<Hits> = needles.iter().map(|_| Hits::default()).collect();
+
+    for (index, data) in haystacks.iter().enumerate() {
+        let active: Vec<usize> = (0..needles.len())
+            .filter(|&n| needles[n].haystacks.contains(&index) && !needles[n].value.is_empty())
+            .collect();
+
+        for chunk_start in (0..data.len()).step_by(CHUNK_SIZE) {
+            let chunk_end = (chunk_start + CHUNK_SIZE).min(data.len());
+
+            for &n in &active {
+                let needle = &needles[n];
+                let started = Instant::now();
+                let record = |start: usize, hits: &mut Hits| {
+                    hits.count += 1;
+                    if hits.listed.len() < max_listed {
+                        hits.listed.push((index, start));
+                    }
+                };
+
+                // Starts in [chunk_start, chunk_end) belong to this chunk;
+                // verification may read past its end.
+                match anchors[n] {
+                    Some(anchor) => {
+                        let from = chunk_start + anchor;
+                        let to = (chunk_end + anchor).min(data.len());
+                        if from < to {
+                            for position in memchr_iter(needle.value[anchor], &data[from..to]) {
+                                let start = chunk_start + position;
+                                if needle.matches_at(data, start) {
+                                    record(start, &mut hits[n]);
+                                }
+                            }
+                        }
+                    }
+                    None => {
+                        for start in chunk_start..chunk_end {
+                            if needle.matches_at(data, start) {
+                                record(start, &mut hits[n]);
+                            }
+                        }
+                    }
+                }
+                hits[n].elapsed += started.elapsed();
+            }
+        }
+    }
+
+    hits
+}
+
+// Approximate byte histogram of the input, taken from a sparse sample.
+fn byte_frequency(haystacks: &[&[u8]]) -> [u32; 256] {
+    let mut frequency = [0u32; 256];
+    for data in haystacks {
+        for &byte in data.iter().step_by(SAMPLE_STRIDE) {
+            frequency[byte as usize] = frequency[byte as usize].saturating_add(1);
+        }
+    }
+    frequency
+}
+
+fn choose_anchor(needle: &Needle, frequency: &[u32; 256]) -> Option<usize> {
+    needle
+        .mask
+        .iter()
+        .enumerate()
+        .filter(|(_, &mask)| mask == 0xFF)
+        .min_by_key(|&(i, _)| frequency[needle.value[i] as usize])
+        .map(|(i, _)| i)
+}
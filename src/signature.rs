@@ -0,0 +1,139 @@
+use crate::arm64::instruction_pattern;
+
+// A parsed AoB signature. A byte matches when `byte & mask == value`, so
+// wildcards can cover whole bytes or single bits.
+//
+// Tokens are separated by whitespace:
+//   "48"          exact byte
+//   "?" or "??"   any byte
+//   "4?" / "?B"   one nibble fixed, the other free
+//   "0b1001_01??" bits, most significant first; "?" bits are free
+//   "AB&F0"       byte & mask: only the bits set in F0 must match AB
+//   "[adrp x8]"   an AArch64 instruction with its immediates free
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub value: Vec<u8>,
+    pub mask: Vec<u8>,
+}
+
+impl Signature {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut signature = Signature {
+            value: Vec::new(),
+            mask: Vec::new(),
+        };
+        for token in tokens(text)? {
+            signature.push_token(token)?;
+        }
+
+        if signature.value.is_empty() {
+            return Err("signature is empty".to_string());
+        }
+        if signature.mask.iter().all(|&m| m == 0) {
+            return Err("signature is only wildcards".to_string());
+        }
+        Ok(signature)
+    }
+
+    fn push_token(&mut self, token: &str) -> Result<(), String> {
+        if let Some(instruction) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            let (value, mask) = instruction_pattern(instruction)
+                .map_err(|e| format!("invalid instruction '{}': {}", token, e))?;
+            self.value.extend_from_slice(&value.to_le_bytes());
+            self.mask.extend_from_slice(&mask.to_le_bytes());
+            return Ok(());
+        }
+
+        let (value, mask) = parse_byte(token)
+            .ok_or_else(|| format!("invalid signature byte '{}'", token))?;
+        self.value.push(value & mask);
+        self.mask.push(mask);
+        Ok(())
+    }
+}
+
+fn parse_byte(token: &str) -> Option<(u8, u8)> {
+    if token == "?" || token == "??" {
+        return Some((0, 0));
+    }
+
+    // "0b" on its own is the hex byte 0x0B.
+    if let Some(bits) = token.strip_prefix("0b").filter(|_| token.len() > 2) {
+        let bits: Vec<char> = bits.chars().filter(|&c| c != '_').collect();
+        if bits.len() != 8 {
+            return None;
+        }
+        let (mut value, mut mask) = (0u8, 0u8);
+        for bit in bits {
+            value <<= 1;
+            mask <<= 1;
+            match bit {
+                '0' => mask |= 1,
+                '1' => {
+                    value |= 1;
+                    mask |= 1;
+                }
+                '?' => {}
+                _ => return None,
+            }
+        }
+        return Some((value, mask));
+    }
+
+    if let Some((value, mask)) = token.split_once('&') {
+        if value.len() != 2 || mask.len() != 2 {
+            return None;
+        }
+        return Some((u8::from_str_radix(value, 16).ok()?, u8::from_str_radix(mask, 16).ok()?));
+    }
+
+    if token.len() != 2 {
+        return None;
+    }
+    let mut chars = token.chars();
+    let (high, low) = (chars.next()?, chars.next()?);
+    let nibble = |c: char| match c {
+        '?' => Some((0, 0)),
+        _ => c.to_digit(16).map(|d| (d as u8, 0xF)),
+    };
+    let (high_value, high_mask) = nibble(high)?;
+    let (low_value, low_mask) = nibble(low)?;
+    Some(((high_value << 4) | low_value, (high_mask << 4) | low_mask))
+}
+
+// Splits on whitespace, keeping a bracketed instruction together.
+fn tokens(text: &str) -> Result<Vec<&str>, String> {
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' => {
+                depth += 1;
+                start.get_or_insert(i);
+            }
+            ']' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| format!("unmatched ']' at column {}", i + 1))?;
+            }
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(s) = start.take() {
+                    tokens.push(&text[s..i]);
+                }
+            }
+            _ => {
+                start.get_or_insert(i);
+            }
+        }
+    }
+
+    if depth > 0 {
+        return Err("unclosed '[' in signature".to_string());
+    }
+    if let Some(s) = start {
+        tokens.push(&text[s..]);
+    }
+    Ok(tokens)
+}
@@ -1,4 +1,7 @@
+use crate::lint::{LintIssue, Severity};
 use crate::models::{GameVariant, OffsetResult};
+use crate::remap::{Rename, TargetChange};
+use crate::scanner::BatchEntry;
 use colored::*;
 use std::io::{self, Write};
 
@@ -29,7 +32,7 @@ pub fn get_game_selection() -> Option<usize> {
     input.trim().parse::<usize>().ok()
 }
 
-pub fn print_results(results: &[OffsetResult], game: GameVariant) {
+pub fn print_results(results: &[OffsetResult], game: GameVariant, verbose: bool) {
     println!("\n{}", format!("====== {} OFFSETS ======", game.name().to_uppercase()).bright_cyan().bold());
     
     let mut current_category = String::new();
@@ -41,18 +44,59 @@ pub fn print_results(results: &[OffsetResult], game: GameVariant) {
         }
         
         match &result.offset {
-            Some(offset) => {
-                println!("{} {}", 
-                    result.name.bright_green().bold(), 
-                    offset.bright_yellow()
+            Some(offset) if result.ambiguous => {
+                let mut count = format!("{} candidates", result.match_count);
+                if result.candidates.len() < result.match_count {
+                    count.push_str(&format!(", first {} listed", result.candidates.len()));
+                }
+                println!("{} {} {}",
+                    result.name.bright_yellow().bold(),
+                    offset.to_string().bright_yellow(),
+                    format!("AMBIGUOUS ({})", count).yellow()
                 );
+                for candidate in &result.candidates {
+                    println!("    {} {} {} {}",
+                        candidate.location.to_string().bright_black(),
+                        candidate.offset.to_string().bright_yellow(),
+                        candidate.class.as_deref().unwrap_or("-").bright_white(),
+                        candidate.text.bright_black()
+                    );
+                }
             }
-            None => {
-                println!("{} {}", 
-                    result.name.bright_red().bold(), 
-                    "NOT FOUND".red()
+            Some(offset) => {
+                println!("{} {} {}", 
+                    result.name.bright_green().bold(), 
+                    offset.to_string().bright_yellow(),
+                    format!("({})", result.provenance.summary()).bright_black()
                 );
+                if verbose {
+                    if let Some(text) = &result.provenance.text {
+                        println!("    {}", text.bright_black());
+                    }
+                }
             }
+            None => match &result.error {
+                Some(error) => {
+                    println!("{} {} {}",
+                        result.name.bright_red().bold(),
+                        "INVALID".red(),
+                        error.bright_black()
+                    );
+                    for candidate in &result.candidates {
+                        println!("    {} {} {}",
+                            candidate.location.to_string().bright_black(),
+                            candidate.offset.to_string().bright_red(),
+                            candidate.text.bright_black()
+                        );
+                    }
+                }
+                None => {
+                    println!("{} {}", 
+                        result.name.bright_red().bold(), 
+                        "NOT FOUND".red()
+                    );
+                }
+            },
         }
     }
 }
@@ -61,6 +105,7 @@ pub fn print_statistics(results: &[OffsetResult]) {
     let found_count = results.iter().filter(|r| r.offset.is_some()).count();
     let total_count = results.len();
     let missing_count = total_count - found_count;
+    let ambiguous_count = results.iter().filter(|r| r.ambiguous).count();
     
     println!("\n{}", "═══════════════════════════════════════════".bright_cyan());
     println!("{}", "STATISTICS".bright_cyan().bold());
@@ -77,6 +122,12 @@ pub fn print_statistics(results: &[OffsetResult]) {
         "Missing:".bright_red().bold(),
         missing_count.to_string().bright_red().bold()
     );
+    if ambiguous_count > 0 {
+        println!("{} {}",
+            "Ambiguous:".bright_yellow().bold(),
+            ambiguous_count.to_string().bright_yellow().bold()
+        );
+    }
     
     let percentage = (found_count as f32 / total_count as f32) * 100.0;
     println!("{} {:.1}%", 
@@ -86,6 +137,144 @@ pub fn print_statistics(results: &[OffsetResult]) {
     println!("{}", "═══════════════════════════════════════════".bright_cyan());
 }
 
+pub fn print_timings(results: &[OffsetResult]) {
+    let mut timed: Vec<&OffsetResult> = results.iter().collect();
+    timed.sort_by_key(|result| std::cmp::Reverse(result.elapsed));
+    let total: std::time::Duration = results.iter().map(|r| r.elapsed).sum();
+
+    println!("\n{}", "TIMINGS".bright_cyan().bold());
+    for result in timed {
+        println!("{:>12} {} {}",
+            format!("{:.3} ms", result.elapsed.as_secs_f64() * 1000.0).bright_yellow(),
+            result.name.bright_white(),
+            format!("({})", result.provenance.kind.name()).bright_black()
+        );
+    }
+    println!("{:>12} {}",
+        format!("{:.3} ms", total.as_secs_f64() * 1000.0).bright_yellow().bold(),
+        "total".bright_white().bold()
+    );
+}
+
+// One row per file: found/missing/invalid/ambiguous counts and scan time,
+// or the error that stopped the file from being scanned.
+pub fn print_batch_summary(entries: &[BatchEntry<(GameVariant, Vec<OffsetResult>)>]) {
+    let width = entries.iter().map(|e| e.path.chars().count()).max().unwrap_or(0).max(4);
+    let (mut found_total, mut missing_total, mut failed_files) = (0, 0, 0);
+
+    println!("\n{}", "═══════════════════════════════════════════".bright_cyan());
+    println!("{}", "BATCH SUMMARY".bright_cyan().bold());
+    println!("{}", "═══════════════════════════════════════════".bright_cyan());
+    println!("{:<width$} {:>6} {:>8} {:>8} {:>10} {:>10}",
+        "File", "Found", "Missing", "Invalid", "Ambiguous", "Time", width = width);
+
+    for entry in entries {
+        let path = &entry.path;
+        let time = format!("{:.2}s", entry.elapsed.as_secs_f64());
+        let results = match &entry.result {
+            Ok((_, results)) => results,
+            Err(error) => {
+                failed_files += 1;
+                println!("{:<width$} {} {}", path, "ERROR".red().bold(), error.bright_black(), width = width);
+                continue;
+            }
+        };
+        let found = results.iter().filter(|r| r.offset.is_some()).count();
+        let invalid = results.iter().filter(|r| r.error.is_some()).count();
+        let ambiguous = results.iter().filter(|r| r.ambiguous).count();
+        found_total += found;
+        missing_total += results.len() - found;
+
+        println!("{:<width$} {:>6} {:>8} {:>8} {:>10} {:>10}",
+            path,
+            found.to_string().bright_green(),
+            (results.len() - found).to_string().bright_red(),
+            invalid.to_string().red(),
+            ambiguous.to_string().bright_yellow(),
+            time.bright_black(),
+            width = width
+        );
+    }
+
+    println!("{}", "═══════════════════════════════════════════".bright_cyan());
+    println!("{} {}   {} {}   {} {}",
+        "Files:".bright_white(), entries.len().to_string().bright_yellow().bold(),
+        "Found:".bright_green().bold(), found_total.to_string().bright_green().bold(),
+        "Missing:".bright_red().bold(), missing_total.to_string().bright_red().bold()
+    );
+    if failed_files > 0 {
+        println!("{} {}", "Failed files:".red().bold(), failed_files.to_string().red().bold());
+    }
+}
+
+pub fn print_lint_report(set_name: &str, issues: &[LintIssue]) {
+    if issues.is_empty() {
+        println!("{} {}", "✓".bright_green().bold(), set_name.bright_white());
+        return;
+    }
+
+    println!("{} {}", "•".bright_blue().bold(), set_name.bright_white().bold());
+    for issue in issues {
+        match issue.severity {
+            Severity::Error => println!("    {} {}", "error:".bright_red().bold(), issue),
+            Severity::Warning => println!("    {} {}", "warning:".bright_yellow().bold(), issue),
+        }
+    }
+}
+
+pub fn print_renames(renames: &[Rename], min_confidence: f32) {
+    let kind_width = 6;
+    let old_width = renames.iter().map(|r| r.old.len()).max().unwrap_or(0).max(3);
+    let new_width = renames.iter().map(|r| r.new.len()).max().unwrap_or(0).max(3);
+
+    println!("\n{}", "═══════════════════════════════════════════".bright_cyan());
+    println!("{}", "RENAMES".bright_cyan().bold());
+    println!("{}", "═══════════════════════════════════════════".bright_cyan());
+    println!("{:<kind_width$} {:<old_width$} {:<new_width$} {:>5}  In",
+        "Kind", "Old", "New", "Conf");
+
+    for rename in renames {
+        let confidence = format!("{:.2}", rename.confidence);
+        let confidence = if rename.confidence >= min_confidence {
+            confidence.bright_green()
+        } else if rename.confidence >= min_confidence / 2.0 {
+            confidence.bright_yellow()
+        } else {
+            confidence.bright_red()
+        };
+        println!("{:<kind_width$} {:<old_width$} {:<new_width$} {:>5}  {}",
+            rename.kind.name(),
+            rename.old,
+            rename.new.bright_white(),
+            confidence,
+            rename.class.bright_black()
+        );
+    }
+
+    let confident = renames.iter().filter(|r| r.confidence >= min_confidence).count();
+    println!("{}", "═══════════════════════════════════════════".bright_cyan());
+    println!("{} {}   {} {}   {} {}",
+        "Renames:".bright_white(), renames.len().to_string().bright_yellow().bold(),
+        format!("≥ {:.2}:", min_confidence).bright_green().bold(), confident.to_string().bright_green().bold(),
+        "Below:".bright_red().bold(), (renames.len() - confident).to_string().bright_red().bold()
+    );
+}
+
+pub fn print_target_changes(changes: &[TargetChange], warnings: &[String]) {
+    println!("\n{}", "Target changes:".bright_blue().bold());
+    if changes.is_empty() {
+        println!("  {}", "nothing to rename".bright_black());
+    }
+    for change in changes {
+        println!("  {} {}", "•".bright_blue(), change.target.bright_white().bold());
+        println!("    {} {}", "-".bright_red(), change.before.bright_red());
+        println!("    {} {}", "+".bright_green(), change.after.bright_green());
+    }
+    for warning in warnings {
+        println!("  {} {}", "warning:".bright_yellow().bold(), warning);
+    }
+}
+
 pub fn print_export_menu() {
     println!("\n{}", "Export options:".bright_blue().bold());
     println!("  {} JSON format", "1.".bright_yellow());